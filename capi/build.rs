@@ -0,0 +1,17 @@
+use std::env;
+use std::path::PathBuf;
+
+// the header is written to OUT_DIR rather than the source tree, so building
+// never dirties the checkout. `include/jif.h` is a checked-in copy, kept up to
+// date by the `header` test
+fn main() {
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    cbindgen::generate(&crate_dir)
+        .expect("Failed to generate C bindings.")
+        .write_to_file(out_dir.join("jif.h"));
+}
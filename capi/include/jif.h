@@ -0,0 +1,267 @@
+#ifndef JIF_H
+#define JIF_H
+
+/* This file is generated by cbindgen from capi/src/lib.rs. Do not edit it by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The largest canvas, in pixels, that [`jif_decoder_open`] accepts: 8192x4096,
+ * or 128 MiB of RGBA pixels.
+ */
+#define JIF_DEFAULT_MAX_CANVAS_AREA (8192 * 4096)
+
+/**
+ * How [`JifFrame::delay_ms`] is computed from the delay time of a frame.
+ */
+typedef enum JifDelayPolicy {
+  /**
+   * Match browsers, which display frames with a delay time of 0 or 1, or
+   * with no delay time at all, for 100 ms.
+   */
+  JIF_DELAY_POLICY_BROWSER_COMPATIBLE,
+  /**
+   * Use the delay time as it appears in the data stream.
+   */
+  JIF_DELAY_POLICY_RAW,
+  /**
+   * Display every frame for at least `min_delay_ms` milliseconds.
+   */
+  JIF_DELAY_POLICY_MINIMUM,
+} JifDelayPolicy;
+
+/**
+ * The layout of the pixels written by [`jif_frame_iterator_next`].
+ */
+typedef enum JifPixelFormat {
+  JIF_PIXEL_FORMAT_RGBA8,
+  JIF_PIXEL_FORMAT_BGRA8,
+  JIF_PIXEL_FORMAT_RGB8,
+  JIF_PIXEL_FORMAT_PREMULTIPLIED_RGBA8,
+  JIF_PIXEL_FORMAT_GRAY8,
+} JifPixelFormat;
+
+typedef enum JifStatus {
+  /**
+   * A frame was written.
+   */
+  JIF_STATUS_OK,
+  /**
+   * Every frame has been decoded. Nothing was written.
+   */
+  JIF_STATUS_DONE,
+  /**
+   * Decoding failed. The error out parameter holds the reason.
+   */
+  JIF_STATUS_ERROR,
+} JifStatus;
+
+/**
+ * A parsed GIF data stream.
+ */
+typedef struct JifDecoder JifDecoder;
+
+/**
+ * An error returned by a `jif` function.
+ */
+typedef struct JifError JifError;
+
+/**
+ * Composites the frames of a [`JifDecoder`] one at a time. An iterator keeps
+ * the data stream alive, so it may outlive the decoder it was created from.
+ */
+typedef struct JifFrameIterator JifFrameIterator;
+
+/**
+ * Configures the frames composited by a [`JifFrameIterator`].
+ */
+typedef struct JifFrameOptions {
+  enum JifPixelFormat pixel_format;
+  /**
+   * Number of bytes between the start of consecutive rows. 0 uses the
+   * width of the canvas times the bytes per pixel.
+   */
+  size_t stride;
+  /**
+   * Composites frames onto a transparent canvas instead of one filled with
+   * the background color.
+   */
+  bool transparent_background;
+  enum JifDelayPolicy delay_policy;
+  /**
+   * The shortest delay, in milliseconds, under
+   * [`JifDelayPolicy::Minimum`]. Ignored by the other policies.
+   */
+  uint32_t min_delay_ms;
+} JifFrameOptions;
+
+/**
+ * Describes a frame written by [`jif_frame_iterator_next`].
+ */
+typedef struct JifFrame {
+  /**
+   * Position of the frame in the data stream.
+   */
+  size_t index;
+  /**
+   * Delay time of the frame in hundredths of a second, as it appears in
+   * the data stream. 0 if the frame has none.
+   */
+  uint16_t delay_time;
+  /**
+   * How long the frame should be displayed for, in milliseconds, as
+   * computed by the iterator's delay policy.
+   */
+  uint32_t delay_ms;
+} JifFrame;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Parses a GIF from `len` bytes at `data`. The bytes are copied, so they
+ * don't need to outlive the decoder. GIFs whose canvas or frames have more
+ * than [`JIF_DEFAULT_MAX_CANVAS_AREA`] pixels are rejected.
+ *
+ * Returns null on failure.
+ *
+ * # Safety
+ *
+ * `data` must point to `len` readable bytes. `error` must be null or point to
+ * a writable `JifError *`.
+ */
+struct JifDecoder *jif_decoder_open(const uint8_t *data, size_t len, struct JifError **error);
+
+/**
+ * Like [`jif_decoder_open`], but rejects GIFs whose canvas or frames have
+ * more than `max_canvas_area` pixels, so that untrusted GIFs can't exhaust
+ * memory.
+ *
+ * Returns null on failure.
+ *
+ * # Safety
+ *
+ * `data` must point to `len` readable bytes. `error` must be null or point to
+ * a writable `JifError *`.
+ */
+struct JifDecoder *jif_decoder_open_with_max_canvas_area(const uint8_t *data,
+                                                         size_t len,
+                                                         size_t max_canvas_area,
+                                                         struct JifError **error);
+
+/**
+ * Releases a decoder. Does nothing if `decoder` is null.
+ *
+ * # Safety
+ *
+ * `decoder` must be null or a decoder returned by [`jif_decoder_open`] that
+ * has not been freed.
+ */
+void jif_decoder_free(struct JifDecoder *decoder);
+
+/**
+ * Width of the canvas, in pixels.
+ *
+ * # Safety
+ *
+ * `decoder` must be a valid decoder.
+ */
+uint16_t jif_decoder_width(const struct JifDecoder *decoder);
+
+/**
+ * Height of the canvas, in pixels.
+ *
+ * # Safety
+ *
+ * `decoder` must be a valid decoder.
+ */
+uint16_t jif_decoder_height(const struct JifDecoder *decoder);
+
+/**
+ * Number of frames in the data stream.
+ *
+ * # Safety
+ *
+ * `decoder` must be a valid decoder.
+ */
+size_t jif_decoder_frame_count(const struct JifDecoder *decoder);
+
+/**
+ * Creates an iterator over the frames of a decoder. `options` may be null,
+ * in which case frames are written as RGBA onto the background color, with
+ * browser compatible delays.
+ *
+ * Returns null on failure.
+ *
+ * # Safety
+ *
+ * `decoder` must be a valid decoder. `options` must be null or point to a
+ * valid `JifFrameOptions`. `error` must be null or point to a writable
+ * `JifError *`.
+ */
+struct JifFrameIterator *jif_decoder_frames(const struct JifDecoder *decoder,
+                                            const struct JifFrameOptions *options,
+                                            struct JifError **error);
+
+/**
+ * Composites the next frame into the `len` bytes at `out`, which must hold
+ * `stride * height` bytes. `frame` may be null; otherwise it is filled in
+ * when a frame is written.
+ *
+ * # Safety
+ *
+ * `iterator` must be a valid frame iterator. `out` must point to `len`
+ * writable bytes. `frame` must be null or point to a writable `JifFrame`.
+ * `error` must be null or point to a writable `JifError *`.
+ */
+enum JifStatus jif_frame_iterator_next(struct JifFrameIterator *iterator,
+                                       uint8_t *out,
+                                       size_t len,
+                                       struct JifFrame *frame,
+                                       struct JifError **error);
+
+/**
+ * Releases a frame iterator. Does nothing if `iterator` is null.
+ *
+ * # Safety
+ *
+ * `iterator` must be null or an iterator returned by [`jif_decoder_frames`]
+ * that has not been freed.
+ */
+void jif_frame_iterator_free(struct JifFrameIterator *iterator);
+
+/**
+ * Number of bytes a pixel takes up in the given format.
+ */
+size_t jif_pixel_format_bytes_per_pixel(enum JifPixelFormat pixel_format);
+
+/**
+ * The message of an error, as a nul-terminated string that lives as long as
+ * the error.
+ *
+ * # Safety
+ *
+ * `error` must be a valid error.
+ */
+const char *jif_error_message(const struct JifError *error);
+
+/**
+ * Releases an error. Does nothing if `error` is null.
+ *
+ * # Safety
+ *
+ * `error` must be null or an error returned by a `jif` function that has not
+ * been freed.
+ */
+void jif_error_free(struct JifError *error);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* JIF_H */
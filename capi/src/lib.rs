@@ -0,0 +1,420 @@
+//! C bindings for `jif`.
+//!
+//! Every object is handed out as an opaque pointer that must be released with
+//! its matching `_free` function. Functions that can fail take a `JifError **`
+//! out parameter, which may be null. On failure it is set to an error that the
+//! caller owns and releases with [`jif_error_free`].
+
+use std::ffi::{c_char, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+use std::sync::Arc;
+use std::time::Duration;
+
+use jif::gif_data_stream::GifDataStream;
+use jif::{Decoder, DelayPolicy, FrameDecoder, PixelFormat};
+
+/// The largest canvas, in pixels, that [`jif_decoder_open`] accepts: 8192x4096,
+/// or 128 MiB of RGBA pixels.
+pub const JIF_DEFAULT_MAX_CANVAS_AREA: usize = 8192 * 4096;
+
+/// A parsed GIF data stream.
+pub struct JifDecoder {
+    gif: Arc<GifDataStream>,
+    frame_count: usize,
+}
+
+/// Composites the frames of a [`JifDecoder`] one at a time. An iterator keeps
+/// the data stream alive, so it may outlive the decoder it was created from.
+pub struct JifFrameIterator {
+    decoder: FrameDecoder<Arc<GifDataStream>>,
+    delay_policy: DelayPolicy,
+}
+
+/// An error returned by a `jif` function.
+pub struct JifError {
+    message: CString,
+}
+
+/// The layout of the pixels written by [`jif_frame_iterator_next`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JifPixelFormat {
+    Rgba8,
+    Bgra8,
+    Rgb8,
+    PremultipliedRgba8,
+    Gray8,
+}
+
+/// How [`JifFrame::delay_ms`] is computed from the delay time of a frame.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JifDelayPolicy {
+    /// Match browsers, which display frames with a delay time of 0 or 1, or
+    /// with no delay time at all, for 100 ms.
+    BrowserCompatible,
+
+    /// Use the delay time as it appears in the data stream.
+    Raw,
+
+    /// Display every frame for at least `min_delay_ms` milliseconds.
+    Minimum,
+}
+
+/// Configures the frames composited by a [`JifFrameIterator`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct JifFrameOptions {
+    pub pixel_format: JifPixelFormat,
+
+    /// Number of bytes between the start of consecutive rows. 0 uses the
+    /// width of the canvas times the bytes per pixel.
+    pub stride: usize,
+
+    /// Composites frames onto a transparent canvas instead of one filled with
+    /// the background color.
+    pub transparent_background: bool,
+
+    pub delay_policy: JifDelayPolicy,
+
+    /// The shortest delay, in milliseconds, under
+    /// [`JifDelayPolicy::Minimum`]. Ignored by the other policies.
+    pub min_delay_ms: u32,
+}
+
+/// Describes a frame written by [`jif_frame_iterator_next`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JifFrame {
+    /// Position of the frame in the data stream.
+    pub index: usize,
+
+    /// Delay time of the frame in hundredths of a second, as it appears in
+    /// the data stream. 0 if the frame has none.
+    pub delay_time: u16,
+
+    /// How long the frame should be displayed for, in milliseconds, as
+    /// computed by the iterator's delay policy.
+    pub delay_ms: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JifStatus {
+    /// A frame was written.
+    Ok,
+
+    /// Every frame has been decoded. Nothing was written.
+    Done,
+
+    /// Decoding failed. The error out parameter holds the reason.
+    Error,
+}
+
+impl From<JifPixelFormat> for PixelFormat {
+    fn from(pixel_format: JifPixelFormat) -> Self {
+        match pixel_format {
+            JifPixelFormat::Rgba8 => Self::Rgba8,
+            JifPixelFormat::Bgra8 => Self::Bgra8,
+            JifPixelFormat::Rgb8 => Self::Rgb8,
+            JifPixelFormat::PremultipliedRgba8 => Self::PremultipliedRgba8,
+            JifPixelFormat::Gray8 => Self::Gray8,
+        }
+    }
+}
+
+impl Default for JifFrameOptions {
+    fn default() -> Self {
+        Self {
+            pixel_format: JifPixelFormat::Rgba8,
+            stride: 0,
+            transparent_background: false,
+            delay_policy: JifDelayPolicy::BrowserCompatible,
+            min_delay_ms: 0,
+        }
+    }
+}
+
+impl JifFrameOptions {
+    fn delay_policy(&self) -> DelayPolicy {
+        match self.delay_policy {
+            JifDelayPolicy::BrowserCompatible => DelayPolicy::BrowserCompatible,
+            JifDelayPolicy::Raw => DelayPolicy::Raw,
+            JifDelayPolicy::Minimum => {
+                DelayPolicy::Minimum(Duration::from_millis(self.min_delay_ms as u64))
+            }
+        }
+    }
+}
+
+impl JifError {
+    fn new(err: jif::Error) -> Self {
+        let message = CString::new(err.message().replace('\0', ""))
+            .expect("Nul bytes were removed from the message.");
+
+        Self { message }
+    }
+}
+
+/// Parses a GIF from `len` bytes at `data`. The bytes are copied, so they
+/// don't need to outlive the decoder. GIFs whose canvas or frames have more
+/// than [`JIF_DEFAULT_MAX_CANVAS_AREA`] pixels are rejected.
+///
+/// Returns null on failure.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes. `error` must be null or point to
+/// a writable `JifError *`.
+#[no_mangle]
+pub unsafe extern "C" fn jif_decoder_open(
+    data: *const u8,
+    len: usize,
+    error: *mut *mut JifError,
+) -> *mut JifDecoder {
+    unsafe { jif_decoder_open_with_max_canvas_area(data, len, JIF_DEFAULT_MAX_CANVAS_AREA, error) }
+}
+
+/// Like [`jif_decoder_open`], but rejects GIFs whose canvas or frames have
+/// more than `max_canvas_area` pixels, so that untrusted GIFs can't exhaust
+/// memory.
+///
+/// Returns null on failure.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes. `error` must be null or point to
+/// a writable `JifError *`.
+#[no_mangle]
+pub unsafe extern "C" fn jif_decoder_open_with_max_canvas_area(
+    data: *const u8,
+    len: usize,
+    max_canvas_area: usize,
+    error: *mut *mut JifError,
+) -> *mut JifDecoder {
+    let result = guard(|| {
+        if data.is_null() {
+            return Err(jif::Error::new("Data is null."));
+        }
+
+        let data = unsafe { slice::from_raw_parts(data, len) };
+        let gif = Decoder::new(data.to_vec())
+            .with_max_canvas_area(max_canvas_area)
+            .parse()?;
+        let frame_count = gif.frame_decoder()?.frames().len();
+
+        Ok(JifDecoder {
+            gif: Arc::new(gif),
+            frame_count,
+        })
+    });
+
+    match result {
+        Ok(decoder) => Box::into_raw(Box::new(decoder)),
+        Err(err) => {
+            unsafe { set_error(error, err) };
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Releases a decoder. Does nothing if `decoder` is null.
+///
+/// # Safety
+///
+/// `decoder` must be null or a decoder returned by [`jif_decoder_open`] that
+/// has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn jif_decoder_free(decoder: *mut JifDecoder) {
+    if !decoder.is_null() {
+        drop(unsafe { Box::from_raw(decoder) });
+    }
+}
+
+/// Width of the canvas, in pixels.
+///
+/// # Safety
+///
+/// `decoder` must be a valid decoder.
+#[no_mangle]
+pub unsafe extern "C" fn jif_decoder_width(decoder: *const JifDecoder) -> u16 {
+    unsafe { &*decoder }
+        .gif
+        .logical_screen_descriptor
+        .canvas_width
+}
+
+/// Height of the canvas, in pixels.
+///
+/// # Safety
+///
+/// `decoder` must be a valid decoder.
+#[no_mangle]
+pub unsafe extern "C" fn jif_decoder_height(decoder: *const JifDecoder) -> u16 {
+    unsafe { &*decoder }
+        .gif
+        .logical_screen_descriptor
+        .canvas_height
+}
+
+/// Number of frames in the data stream.
+///
+/// # Safety
+///
+/// `decoder` must be a valid decoder.
+#[no_mangle]
+pub unsafe extern "C" fn jif_decoder_frame_count(decoder: *const JifDecoder) -> usize {
+    unsafe { &*decoder }.frame_count
+}
+
+/// Creates an iterator over the frames of a decoder. `options` may be null,
+/// in which case frames are written as RGBA onto the background color, with
+/// browser compatible delays.
+///
+/// Returns null on failure.
+///
+/// # Safety
+///
+/// `decoder` must be a valid decoder. `options` must be null or point to a
+/// valid `JifFrameOptions`. `error` must be null or point to a writable
+/// `JifError *`.
+#[no_mangle]
+pub unsafe extern "C" fn jif_decoder_frames(
+    decoder: *const JifDecoder,
+    options: *const JifFrameOptions,
+    error: *mut *mut JifError,
+) -> *mut JifFrameIterator {
+    let decoder = unsafe { &*decoder };
+    let options = unsafe { options.as_ref() }.copied().unwrap_or_default();
+
+    let result = guard(|| {
+        let mut frame_decoder = FrameDecoder::new(Arc::clone(&decoder.gif))?
+            .with_pixel_format(options.pixel_format.into());
+
+        if options.stride != 0 {
+            frame_decoder = frame_decoder.with_stride(options.stride);
+        }
+
+        if options.transparent_background {
+            frame_decoder = frame_decoder.with_transparent_background();
+        }
+
+        Ok(JifFrameIterator {
+            decoder: frame_decoder,
+            delay_policy: options.delay_policy(),
+        })
+    });
+
+    match result {
+        Ok(frames) => Box::into_raw(Box::new(frames)),
+        Err(err) => {
+            unsafe { set_error(error, err) };
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Composites the next frame into the `len` bytes at `out`, which must hold
+/// `stride * height` bytes. `frame` may be null; otherwise it is filled in
+/// when a frame is written.
+///
+/// # Safety
+///
+/// `iterator` must be a valid frame iterator. `out` must point to `len`
+/// writable bytes. `frame` must be null or point to a writable `JifFrame`.
+/// `error` must be null or point to a writable `JifError *`.
+#[no_mangle]
+pub unsafe extern "C" fn jif_frame_iterator_next(
+    iterator: *mut JifFrameIterator,
+    out: *mut u8,
+    len: usize,
+    frame: *mut JifFrame,
+    error: *mut *mut JifError,
+) -> JifStatus {
+    let iterator = unsafe { &mut *iterator };
+
+    let result = guard(|| {
+        if out.is_null() {
+            return Err(jif::Error::new("Output buffer is null."));
+        }
+
+        let out = unsafe { slice::from_raw_parts_mut(out, len) };
+        iterator.decoder.next_frame_into(out)
+    });
+
+    match result {
+        Ok(Some(meta)) => {
+            if let Some(frame) = unsafe { frame.as_mut() } {
+                *frame = JifFrame {
+                    index: meta.index,
+                    delay_time: meta.delay_time.unwrap_or(0),
+                    delay_ms: meta.delay(iterator.delay_policy).as_millis() as u32,
+                };
+            }
+
+            JifStatus::Ok
+        }
+        Ok(None) => JifStatus::Done,
+        Err(err) => {
+            unsafe { set_error(error, err) };
+            JifStatus::Error
+        }
+    }
+}
+
+/// Releases a frame iterator. Does nothing if `iterator` is null.
+///
+/// # Safety
+///
+/// `iterator` must be null or an iterator returned by [`jif_decoder_frames`]
+/// that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn jif_frame_iterator_free(iterator: *mut JifFrameIterator) {
+    if !iterator.is_null() {
+        drop(unsafe { Box::from_raw(iterator) });
+    }
+}
+
+/// Number of bytes a pixel takes up in the given format.
+#[no_mangle]
+pub extern "C" fn jif_pixel_format_bytes_per_pixel(pixel_format: JifPixelFormat) -> usize {
+    PixelFormat::from(pixel_format).bytes_per_pixel()
+}
+
+/// The message of an error, as a nul-terminated string that lives as long as
+/// the error.
+///
+/// # Safety
+///
+/// `error` must be a valid error.
+#[no_mangle]
+pub unsafe extern "C" fn jif_error_message(error: *const JifError) -> *const c_char {
+    unsafe { &*error }.message.as_ptr()
+}
+
+/// Releases an error. Does nothing if `error` is null.
+///
+/// # Safety
+///
+/// `error` must be null or an error returned by a `jif` function that has not
+/// been freed.
+#[no_mangle]
+pub unsafe extern "C" fn jif_error_free(error: *mut JifError) {
+    if !error.is_null() {
+        drop(unsafe { Box::from_raw(error) });
+    }
+}
+
+// unwinding into C is undefined behavior, so panics are reported as errors
+fn guard<T>(f: impl FnOnce() -> jif::Result<T>) -> jif::Result<T> {
+    panic::catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|_| Err(jif::Error::new("jif panicked while decoding.")))
+}
+
+unsafe fn set_error(error: *mut *mut JifError, err: jif::Error) {
+    if !error.is_null() {
+        unsafe { *error = Box::into_raw(Box::new(JifError::new(err))) };
+    }
+}
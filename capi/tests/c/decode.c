@@ -0,0 +1,117 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "jif.h"
+
+#define CHECK(condition, ...)                  \
+    do {                                       \
+        if (!(condition)) {                    \
+            fprintf(stderr, __VA_ARGS__);      \
+            fprintf(stderr, "\n");             \
+            exit(1);                           \
+        }                                      \
+    } while (0)
+
+static uint8_t *read_file(const char *path, size_t *len) {
+    FILE *file = fopen(path, "rb");
+    CHECK(file != NULL, "Failed to open %s.", path);
+
+    fseek(file, 0, SEEK_END);
+    *len = (size_t)ftell(file);
+    fseek(file, 0, SEEK_SET);
+
+    uint8_t *data = malloc(*len);
+    CHECK(fread(data, 1, *len, file) == *len, "Failed to read %s.", path);
+    fclose(file);
+
+    return data;
+}
+
+int main(int argc, char **argv) {
+    CHECK(argc == 2, "Usage: %s <gif>", argv[0]);
+
+    JifError *error = NULL;
+
+    // invalid data is reported through the error
+    const uint8_t invalid[] = "GIF89a";
+    CHECK(jif_decoder_open(invalid, sizeof(invalid) - 1, &error) == NULL, "Opened an invalid GIF.");
+    CHECK(error != NULL && strlen(jif_error_message(error)) > 0, "Missing error message.");
+    jif_error_free(error);
+    error = NULL;
+
+    // a header alone can declare a canvas of 65535x65535 pixels
+    const uint8_t huge[] = "GIF89a\xFF\xFF\xFF\xFF\x00\x00\x00\x3B";
+    CHECK(jif_decoder_open(huge, sizeof(huge) - 1, &error) == NULL, "Opened a GIF with a huge canvas.");
+    jif_error_free(error);
+    error = NULL;
+
+    size_t len;
+    uint8_t *data = read_file(argv[1], &len);
+
+    JifDecoder *limited = jif_decoder_open_with_max_canvas_area(data, len, 1, &error);
+    CHECK(limited == NULL, "Opened a GIF larger than the maximum canvas area.");
+    jif_error_free(error);
+    error = NULL;
+
+    JifDecoder *decoder = jif_decoder_open(data, len, &error);
+    free(data);
+    CHECK(decoder != NULL, "Failed to open GIF: %s", jif_error_message(error));
+
+    uint16_t width = jif_decoder_width(decoder);
+    uint16_t height = jif_decoder_height(decoder);
+    size_t frame_count = jif_decoder_frame_count(decoder);
+    CHECK(width > 0 && height > 0 && frame_count > 0, "Empty GIF.");
+
+    // pad every row to check that the stride is respected
+    size_t row_len = width * jif_pixel_format_bytes_per_pixel(JIF_PIXEL_FORMAT_RGBA8);
+    JifFrameOptions options = {
+        .pixel_format = JIF_PIXEL_FORMAT_RGBA8,
+        .stride = row_len + 4,
+        .transparent_background = false,
+        .delay_policy = JIF_DELAY_POLICY_MINIMUM,
+        .min_delay_ms = 20,
+    };
+
+    JifFrameIterator *frames = jif_decoder_frames(decoder, &options, &error);
+    CHECK(frames != NULL, "Failed to create frame iterator: %s", jif_error_message(error));
+
+    // the iterator keeps the data stream alive on its own
+    jif_decoder_free(decoder);
+
+    size_t buffer_len = options.stride * height;
+    uint8_t *buffer = calloc(buffer_len, 1);
+
+    // too small a buffer is an error, not a crash
+    CHECK(jif_frame_iterator_next(frames, buffer, row_len, NULL, &error) == JIF_STATUS_ERROR,
+          "Decoded into too small a buffer.");
+    jif_error_free(error);
+    error = NULL;
+
+    JifFrame frame;
+    size_t count = 0;
+    JifStatus status;
+
+    while ((status = jif_frame_iterator_next(frames, buffer, buffer_len, &frame, &error)) == JIF_STATUS_OK) {
+        CHECK(frame.index == count, "Expected frame %zu, got %zu.", count, frame.index);
+        CHECK(frame.delay_ms >= 20 && frame.delay_ms >= frame.delay_time * 10u,
+              "Frame %zu is delayed for %u ms.", frame.index, frame.delay_ms);
+
+        for (size_t row = 0; row < height; row++) {
+            for (size_t x = 0; x < width; x++) {
+                CHECK(buffer[row * options.stride + x * 4 + 3] == 0xFF, "Pixel is not opaque.");
+            }
+        }
+
+        count++;
+    }
+
+    CHECK(status == JIF_STATUS_DONE, "Failed to decode frame: %s", jif_error_message(error));
+    CHECK(count == frame_count, "Decoded %zu of %zu frames.", count, frame_count);
+
+    free(buffer);
+    jif_frame_iterator_free(frames);
+
+    printf("Decoded %zu frames of %ux%u.\n", count, width, height);
+    return 0;
+}
@@ -0,0 +1,55 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// cargo builds the static library into the same directory as the test executable
+fn deps_dir() -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    exe.parent().unwrap().to_path_buf()
+}
+
+#[test]
+fn c_program() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let output = Path::new(env!("CARGO_TARGET_TMPDIR")).join("decode");
+
+    let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg(manifest_dir.join("tests/c/decode.c"))
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(env!("OUT_DIR"))
+        .arg(deps_dir().join("libjif_capi.a"))
+        .args(["-lpthread", "-ldl", "-lm"])
+        .arg("-o")
+        .arg(&output)
+        .status()
+        .unwrap();
+    assert!(status.success(), "Failed to compile the C test program.");
+
+    let output = Command::new(&output)
+        .arg(manifest_dir.join("../sample_gifs/sample_2_animation.gif"))
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn header() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let generated = fs::read_to_string(Path::new(env!("OUT_DIR")).join("jif.h")).unwrap();
+    let checked_in = fs::read_to_string(manifest_dir.join("include/jif.h")).unwrap();
+
+    assert!(
+        generated == checked_in,
+        "include/jif.h is out of date. Regenerate it with `cbindgen --config capi/cbindgen.toml \
+         --output capi/include/jif.h capi`."
+    );
+}
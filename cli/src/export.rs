@@ -0,0 +1,416 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::ops::{Bound, RangeBounds};
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use eyre::{eyre, Result};
+use serde_json::json;
+
+use jif::gif_data_stream::GifDataStream;
+use jif::grammar::Frame;
+use jif::{DelayPolicy, PixelFormat};
+
+/// The file format frames are exported as.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Binary PPM (P6), RGB.
+    Ppm,
+
+    /// PAM (P7), RGBA.
+    Pam,
+
+    /// PNG, RGBA.
+    #[cfg(feature = "png")]
+    Png,
+}
+
+impl ImageFormat {
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Ppm => "ppm",
+            Self::Pam => "pam",
+            #[cfg(feature = "png")]
+            Self::Png => "png",
+        }
+    }
+
+    pub const fn pixel_format(&self) -> PixelFormat {
+        match self {
+            Self::Ppm => PixelFormat::Rgb8,
+            Self::Pam => PixelFormat::Rgba8,
+            #[cfg(feature = "png")]
+            Self::Png => PixelFormat::Rgba8,
+        }
+    }
+
+    pub fn write(
+        &self,
+        out: &mut impl Write,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> Result<()> {
+        match self {
+            Self::Ppm => {
+                write!(out, "P6\n{} {}\n255\n", width, height)?;
+                out.write_all(pixels)?;
+            }
+            Self::Pam => {
+                write!(
+                    out,
+                    "P7\nWIDTH {}\nHEIGHT {}\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n",
+                    width, height
+                )?;
+                out.write_all(pixels)?;
+            }
+            #[cfg(feature = "png")]
+            Self::Png => {
+                let mut encoder = png::Encoder::new(out, width, height);
+                encoder.set_color(png::ColorType::Rgba);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder.write_header()?.write_image_data(pixels)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A range of frame indices, written as `n`, `a..b`, `a..=b`, `a..` or `..b`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameRange {
+    start: Bound<usize>,
+    end: Bound<usize>,
+}
+
+impl RangeBounds<usize> for FrameRange {
+    fn start_bound(&self) -> Bound<&usize> {
+        self.start.as_ref()
+    }
+
+    fn end_bound(&self) -> Bound<&usize> {
+        self.end.as_ref()
+    }
+}
+
+pub fn parse_frame_range(range: &str) -> Result<FrameRange, String> {
+    let parse = |index: &str| {
+        index
+            .parse::<usize>()
+            .map_err(|_| format!("expected a frame index, got `{}`", index))
+    };
+
+    let Some((start, end)) = range.split_once("..") else {
+        let index = parse(range)?;
+
+        return Ok(FrameRange {
+            start: Bound::Included(index),
+            end: Bound::Included(index),
+        });
+    };
+
+    let start = match start {
+        "" => Bound::Unbounded,
+        start => Bound::Included(parse(start)?),
+    };
+
+    let end = match end.strip_prefix('=') {
+        Some(end) => Bound::Included(parse(end)?),
+        None if end.is_empty() => Bound::Unbounded,
+        None => Bound::Excluded(parse(end)?),
+    };
+
+    Ok(FrameRange { start, end })
+}
+
+pub struct Options {
+    pub gif_path: PathBuf,
+    pub output_dir: PathBuf,
+    pub format: ImageFormat,
+    pub frames: Option<FrameRange>,
+    pub template: String,
+    pub manifest: bool,
+    pub transparent_background: bool,
+    pub grow_canvas: bool,
+    pub delay_policy: DelayPolicy,
+}
+
+pub fn run(gif: &GifDataStream, options: Options) -> Result<()> {
+    let Options {
+        gif_path,
+        output_dir,
+        format,
+        frames: range,
+        template,
+        manifest,
+        transparent_background,
+        grow_canvas,
+        delay_policy,
+    } = options;
+
+    let stem = gif_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let mut frame_decoder = gif.frame_decoder()?;
+
+    if transparent_background {
+        frame_decoder = frame_decoder.with_transparent_background();
+    }
+
+    if grow_canvas {
+        frame_decoder = frame_decoder.with_grown_canvas()?;
+    }
+
+    let (width, height) = frame_decoder.canvas_size();
+
+    let frame_count = frame_decoder.frames().len();
+    let indices = (0..frame_count)
+        .filter(|index| range.is_none_or(|range| range.contains(index)))
+        .collect::<Vec<_>>();
+
+    if indices.is_empty() {
+        return Err(eyre!(
+            "No frames to export, data stream has {} frames.",
+            frame_count
+        ));
+    }
+
+    // every frame would be written to the same file
+    if indices.len() > 1 && !template.contains("{index") {
+        return Err(eyre!(
+            "Template `{}` must contain `{{index}}` to export {} frames.",
+            template,
+            indices.len()
+        ));
+    }
+
+    fs::create_dir_all(&output_dir)?;
+
+    let pixel_format = format.pixel_format();
+    let mut pixels = vec![0; width as usize * height as usize * pixel_format.bytes_per_pixel()];
+    let mut entries = vec![];
+
+    for (position, &index) in indices.iter().enumerate() {
+        // only the first frame needs a seek, the rest follow in order
+        let frame = match position {
+            0 => frame_decoder.seek(index)?,
+            _ => frame_decoder
+                .next_frame()?
+                .ok_or_else(|| eyre!("Expected frame {}.", index))?,
+        };
+
+        frame.write_pixels(pixel_format, &mut pixels)?;
+
+        let file_name = render_template(&template, &stem, index, format.extension())?;
+        let mut out = BufWriter::new(File::create(output_dir.join(&file_name))?);
+        format.write(&mut out, width as u32, height as u32, &pixels)?;
+        out.flush()?;
+
+        entries.push(manifest_entry(&frame, index, &file_name, delay_policy));
+    }
+
+    for diagnostic in frame_decoder.diagnostics() {
+        eprintln!("{}: {}", gif_path.display(), diagnostic);
+    }
+
+    if manifest {
+        let manifest = json!({
+            "source": gif_path,
+            "width": width,
+            "height": height,
+            "frames": entries,
+        });
+
+        let path = output_dir.join(format!("{}.json", stem));
+        let mut out = BufWriter::new(File::create(&path)?);
+        serde_json::to_writer_pretty(&mut out, &manifest)?;
+        writeln!(out)?;
+        out.flush()?;
+    }
+
+    println!(
+        "Exported {} frames to {}.",
+        entries.len(),
+        output_dir.display()
+    );
+
+    Ok(())
+}
+
+fn manifest_entry(
+    frame: &Frame,
+    index: usize,
+    file_name: &str,
+    delay_policy: DelayPolicy,
+) -> serde_json::Value {
+    json!({
+        "index": index,
+        "file": file_name,
+        "delay_time": frame.delay_time,
+        "delay_ms": frame.delay(delay_policy).as_millis() as u64,
+    })
+}
+
+/// Fills in a file name template. `{stem}` is the name of the GIF without its
+/// extension, `{ext}` the extension of the exported format, and `{index}` the
+/// index of the frame, which can be zero-padded to a width as `{index:04}`.
+pub fn render_template(template: &str, stem: &str, index: usize, ext: &str) -> Result<String> {
+    let mut file_name = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        file_name.push_str(&rest[..start]);
+
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| eyre!("Unclosed `{{` in template `{}`.", template))?;
+        let placeholder = &rest[start + 1..start + end];
+
+        match placeholder.split_once(':') {
+            None if placeholder == "stem" => file_name.push_str(stem),
+            None if placeholder == "ext" => file_name.push_str(ext),
+            None if placeholder == "index" => file_name.push_str(&index.to_string()),
+            Some(("index", width)) if width.starts_with('0') => {
+                let width = width
+                    .parse::<usize>()
+                    .map_err(|_| eyre!("Invalid width `{}` in template `{}`.", width, template))?;
+                file_name.push_str(&format!("{:0width$}", index, width = width));
+            }
+            _ => {
+                return Err(eyre!(
+                    "Unknown placeholder `{{{}}}` in template `{}`.",
+                    placeholder,
+                    template
+                ))
+            }
+        }
+
+        rest = &rest[start + end + 1..];
+    }
+
+    file_name.push_str(rest);
+
+    if Path::new(&file_name).components().count() != 1 {
+        return Err(eyre!(
+            "Template `{}` must produce a file name, got `{}`.",
+            template,
+            file_name
+        ));
+    }
+
+    Ok(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_range() {
+        let range = |range| parse_frame_range(range).unwrap();
+
+        assert!(range("3").contains(&3));
+        assert!(!range("3").contains(&4));
+        assert!(range("2..5").contains(&4));
+        assert!(!range("2..5").contains(&5));
+        assert!(range("2..=5").contains(&5));
+        assert!(range("2..").contains(&100));
+        assert!(!range("..2").contains(&2));
+        assert!(parse_frame_range("a..b").is_err());
+    }
+
+    #[test]
+    fn template() -> Result<()> {
+        assert_eq!(
+            render_template("{stem}-{index:04}.{ext}", "shrek", 7, "ppm")?,
+            "shrek-0007.ppm"
+        );
+        assert_eq!(
+            render_template("{index}.{ext}", "shrek", 12, "pam")?,
+            "12.pam"
+        );
+        assert!(render_template("{frame}.ppm", "shrek", 0, "ppm").is_err());
+        assert!(render_template("{index.ppm", "shrek", 0, "ppm").is_err());
+        assert!(render_template("../{index}.ppm", "shrek", 0, "ppm").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn export() -> Result<()> {
+        let gif_path = PathBuf::from("../sample_gifs/sample_2_animation.gif");
+        let gif = jif::Decoder::new(jif::dump_gif(gif_path.to_str().unwrap())?).parse()?;
+        let output_dir = std::env::temp_dir().join(format!("jif-export-{}", std::process::id()));
+
+        run(
+            &gif,
+            Options {
+                gif_path,
+                output_dir: output_dir.clone(),
+                format: ImageFormat::Ppm,
+                frames: Some(parse_frame_range("1..").unwrap()),
+                template: "{index}.{ext}".to_string(),
+                manifest: true,
+                transparent_background: false,
+                grow_canvas: false,
+                delay_policy: DelayPolicy::Raw,
+            },
+        )?;
+
+        let expected = gif.decompress()?;
+        let lsd = &gif.logical_screen_descriptor;
+        let header = format!("P6\n{} {}\n255\n", lsd.canvas_width, lsd.canvas_height);
+
+        for (index, frame) in expected.iter().enumerate().skip(1) {
+            let ppm = fs::read(output_dir.join(format!("{}.ppm", index)))?;
+            let mut pixels = vec![0; ppm.len() - header.len()];
+            frame.write_pixels(PixelFormat::Rgb8, &mut pixels)?;
+
+            assert_eq!(&ppm[..header.len()], header.as_bytes());
+            assert_eq!(&ppm[header.len()..], pixels.as_slice());
+        }
+
+        assert!(!output_dir.join("0.ppm").exists());
+
+        let manifest: serde_json::Value =
+            serde_json::from_slice(&fs::read(output_dir.join("sample_2_animation.json"))?)?;
+        assert_eq!(manifest["frames"][0]["file"], "1.ppm");
+        assert_eq!(manifest["frames"][0]["delay_ms"], 500);
+
+        fs::remove_dir_all(output_dir)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn template_without_index() -> Result<()> {
+        let gif_path = PathBuf::from("../sample_gifs/sample_2_animation.gif");
+        let gif = jif::Decoder::new(jif::dump_gif(gif_path.to_str().unwrap())?).parse()?;
+        let output_dir =
+            std::env::temp_dir().join(format!("jif-export-template-{}", std::process::id()));
+        let options = |frames| Options {
+            gif_path: gif_path.clone(),
+            output_dir: output_dir.clone(),
+            format: ImageFormat::Ppm,
+            frames: Some(parse_frame_range(frames).unwrap()),
+            template: "{stem}.{ext}".to_string(),
+            manifest: false,
+            transparent_background: false,
+            grow_canvas: false,
+            delay_policy: DelayPolicy::Raw,
+        };
+
+        assert!(run(&gif, options("0..2")).is_err());
+        assert!(!output_dir.exists());
+
+        run(&gif, options("1"))?;
+        assert!(output_dir.join("sample_2_animation.ppm").exists());
+
+        fs::remove_dir_all(output_dir)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,324 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+use eyre::{eyre, Result};
+use serde_json::{json, Value};
+
+use jif::gif_data_stream::{Block, GifDataStream};
+use jif::grammar::{
+    ApplicationExtension, CommentExtension, GraphicControlExtension, LogicalScreenDescriptor,
+    PlainTextExtension, TableBasedImage,
+};
+use jif::Decoder;
+
+use crate::validate::diagnostic_to_json;
+use crate::Format;
+
+// the blocks before the first one that fails to parse are still printed, with
+// the error listed after the other diagnostics
+pub fn run(gif_path: &Path, format: Format) -> Result<()> {
+    let data = std::fs::read(gif_path)?;
+    let (mut gif, error) = Decoder::new(data).parse_partial()?;
+    let failed = error.is_some();
+    gif.diagnostics.extend(error);
+
+    let mut out = io::stdout().lock();
+
+    match format {
+        Format::Human => write_human(&gif, &mut out)?,
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut out, &to_json(&gif))?;
+            writeln!(out)?;
+        }
+    }
+
+    if failed {
+        return Err(eyre!("Failed to parse {}.", gif_path.display()));
+    }
+
+    Ok(())
+}
+
+fn write_human(gif: &GifDataStream, out: &mut impl Write) -> io::Result<()> {
+    let lsd = &gif.logical_screen_descriptor;
+
+    writeln!(out, "GIF{}", gif.version)?;
+    writeln!(out, "Logical Screen Descriptor")?;
+    writeln!(out, "  canvas: {}x{}", lsd.canvas_width, lsd.canvas_height)?;
+    writeln!(out, "  color resolution: {} bits", lsd.color_resolution())?;
+    writeln!(
+        out,
+        "  background color index: {}",
+        lsd.background_color_index
+    )?;
+    writeln!(out, "  pixel aspect ratio: {}", lsd.pixel_aspect_ratio)?;
+    writeln!(
+        out,
+        "  global color table: {}",
+        color_table(gif.global_color_table.as_deref(), lsd.sort_flag())
+    )?;
+
+    writeln!(out, "Blocks")?;
+
+    for (index, (block, offset)) in gif.blocks.iter().zip(&gif.block_offsets).enumerate() {
+        write!(out, "  [{}] @ {:#010x}  ", index, offset)?;
+
+        match block {
+            Block::GraphicControlExtension(gce) => {
+                writeln!(out, "Graphic Control Extension")?;
+                writeln!(
+                    out,
+                    "      disposal: {}, delay: {} ({} ms), user input: {}",
+                    gce.disposal_method(),
+                    gce.delay_time,
+                    gce.delay_time as u32 * 10,
+                    gce.user_input_flag()
+                )?;
+                writeln!(out, "      transparent color index: {}", transparency(gce))?;
+            }
+            Block::TableBasedImage(tbi) => {
+                let descriptor = &tbi.image_descriptor;
+
+                writeln!(out, "Table-Based Image")?;
+                writeln!(
+                    out,
+                    "      geometry: {}x{} at ({}, {}), interlaced: {}",
+                    descriptor.image_width,
+                    descriptor.image_height,
+                    descriptor.image_left,
+                    descriptor.image_top,
+                    descriptor.interlace_flag()
+                )?;
+                writeln!(
+                    out,
+                    "      local color table: {}",
+                    color_table(tbi.local_color_table.as_deref(), descriptor.sort_flag())
+                )?;
+                writeln!(
+                    out,
+                    "      lzw minimum code size: {}, sub-blocks: {} ({} bytes)",
+                    tbi.lzw_minimum_code,
+                    tbi.image_data.len(),
+                    image_data_len(tbi)
+                )?;
+            }
+            Block::PlainTextExtension(pte) => {
+                writeln!(out, "Plain Text Extension")?;
+                writeln!(
+                    out,
+                    "      grid: {}x{} at ({}, {}), cell: {}x{}, text: {:?}",
+                    pte.text_grid_width,
+                    pte.text_grid_height,
+                    pte.text_grid_left_position,
+                    pte.text_grid_top_position,
+                    pte.character_cell_width,
+                    pte.character_cell_height,
+                    String::from_utf8_lossy(&pte.plain_text_data)
+                )?;
+            }
+            Block::ApplicationExtension(ae) => {
+                writeln!(out, "Application Extension")?;
+                writeln!(
+                    out,
+                    "      identifier: {}{}, data: {} bytes",
+                    ae.identifier,
+                    String::from_utf8_lossy(&ae.authentication_code),
+                    ae.data.len()
+                )?;
+            }
+            Block::CommentExtension(ce) => {
+                writeln!(out, "Comment Extension")?;
+                writeln!(out, "      {:?}", String::from_utf8_lossy(&ce.data))?;
+            }
+        }
+    }
+
+    if !gif.diagnostics.is_empty() {
+        writeln!(out, "Diagnostics")?;
+
+        for diagnostic in &gif.diagnostics {
+            writeln!(out, "  {}", diagnostic)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn color_table(color_table: Option<&[u8]>, sorted: bool) -> String {
+    match color_table {
+        Some(color_table) => format!(
+            "{} colors ({} bytes), sorted: {}",
+            color_table.len() / 3,
+            color_table.len(),
+            sorted
+        ),
+        None => "none".to_string(),
+    }
+}
+
+fn transparency(gce: &GraphicControlExtension) -> String {
+    match gce.transparent_color_flag() {
+        true => gce.transparent_color_index.to_string(),
+        false => "none".to_string(),
+    }
+}
+
+fn image_data_len(tbi: &TableBasedImage) -> usize {
+    tbi.image_data.iter().map(Vec::len).sum()
+}
+
+fn to_json(gif: &GifDataStream) -> Value {
+    let LogicalScreenDescriptor {
+        canvas_width,
+        canvas_height,
+        background_color_index,
+        pixel_aspect_ratio,
+        ..
+    } = gif.logical_screen_descriptor;
+    let lsd = &gif.logical_screen_descriptor;
+
+    let blocks = gif
+        .blocks
+        .iter()
+        .zip(&gif.block_offsets)
+        .map(|(block, offset)| {
+            let mut value = block_to_json(block);
+            value["offset"] = json!(offset);
+            value
+        })
+        .collect::<Vec<_>>();
+
+    let diagnostics = gif
+        .diagnostics
+        .iter()
+        .map(diagnostic_to_json)
+        .collect::<Vec<_>>();
+
+    json!({
+        "version": gif.version.to_string(),
+        "logical_screen_descriptor": {
+            "canvas_width": canvas_width,
+            "canvas_height": canvas_height,
+            "global_color_table_flag": lsd.global_color_table_flag(),
+            "color_resolution": lsd.color_resolution(),
+            "sort_flag": lsd.sort_flag(),
+            "background_color_index": background_color_index,
+            "pixel_aspect_ratio": pixel_aspect_ratio,
+        },
+        "global_color_table": color_table_to_json(gif.global_color_table.as_deref()),
+        "blocks": blocks,
+        "diagnostics": diagnostics,
+    })
+}
+
+fn block_to_json(block: &Block) -> Value {
+    match block {
+        Block::GraphicControlExtension(gce) => json!({
+            "type": "graphic_control_extension",
+            "disposal_method": gce.disposal_method().as_str(),
+            "delay_time": gce.delay_time,
+            "user_input_flag": gce.user_input_flag(),
+            "transparent_color_index": gce
+                .transparent_color_flag()
+                .then_some(gce.transparent_color_index),
+        }),
+        Block::TableBasedImage(tbi) => {
+            let descriptor = &tbi.image_descriptor;
+
+            json!({
+                "type": "table_based_image",
+                "left": descriptor.image_left,
+                "top": descriptor.image_top,
+                "width": descriptor.image_width,
+                "height": descriptor.image_height,
+                "interlace_flag": descriptor.interlace_flag(),
+                "sort_flag": descriptor.sort_flag(),
+                "local_color_table": color_table_to_json(tbi.local_color_table.as_deref()),
+                "lzw_minimum_code": tbi.lzw_minimum_code,
+                "sub_blocks": tbi.image_data.len(),
+                "image_data_len": image_data_len(tbi),
+            })
+        }
+        Block::PlainTextExtension(PlainTextExtension {
+            text_grid_left_position,
+            text_grid_top_position,
+            text_grid_width,
+            text_grid_height,
+            character_cell_width,
+            character_cell_height,
+            text_foreground_color_index,
+            text_background_color_index,
+            plain_text_data,
+        }) => json!({
+            "type": "plain_text_extension",
+            "text_grid_left_position": text_grid_left_position,
+            "text_grid_top_position": text_grid_top_position,
+            "text_grid_width": text_grid_width,
+            "text_grid_height": text_grid_height,
+            "character_cell_width": character_cell_width,
+            "character_cell_height": character_cell_height,
+            "text_foreground_color_index": text_foreground_color_index,
+            "text_background_color_index": text_background_color_index,
+            "text": String::from_utf8_lossy(plain_text_data),
+        }),
+        Block::ApplicationExtension(ApplicationExtension {
+            identifier,
+            authentication_code,
+            data,
+        }) => json!({
+            "type": "application_extension",
+            "identifier": identifier,
+            "authentication_code": String::from_utf8_lossy(authentication_code),
+            "data": data,
+        }),
+        Block::CommentExtension(CommentExtension { data }) => json!({
+            "type": "comment_extension",
+            "text": String::from_utf8_lossy(data),
+        }),
+    }
+}
+
+fn color_table_to_json(color_table: Option<&[u8]>) -> Value {
+    match color_table {
+        Some(color_table) => json!({
+            "colors": color_table.len() / 3,
+            "len": color_table.len(),
+        }),
+        None => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jif::{dump_gif, Decoder};
+
+    use super::*;
+
+    #[test]
+    fn human_and_json_agree() -> Result<()> {
+        let data = dump_gif("../sample_gifs/lady-dance.gif")?;
+        let gif = Decoder::new(data).parse()?;
+
+        let mut human = vec![];
+        write_human(&gif, &mut human)?;
+        let human = String::from_utf8(human)?;
+
+        let json = to_json(&gif);
+        let blocks = json["blocks"].as_array().unwrap();
+        assert_eq!(blocks.len(), gif.blocks.len());
+
+        for (index, block) in blocks.iter().enumerate() {
+            let offset = block["offset"].as_u64().unwrap();
+            assert!(human.contains(&format!("[{}] @ {:#010x}", index, offset)));
+
+            if let Some(disposal_method) = block["disposal_method"].as_str() {
+                assert!(human.contains(&format!("disposal: {},", disposal_method)));
+            }
+        }
+
+        assert_eq!(json["version"], gif.version.to_string());
+        assert!(human.starts_with(&format!("GIF{}\n", gif.version)));
+
+        Ok(())
+    }
+}
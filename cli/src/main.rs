@@ -0,0 +1,199 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use eyre::Result;
+
+use jif::gif_data_stream::GifDataStream;
+use jif::{Decoder, DelayPolicy};
+
+use crate::export::{parse_frame_range, FrameRange, ImageFormat};
+
+mod export;
+mod inspect;
+mod sheet;
+mod validate;
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Prints the block structure of a GIF, up to the first block that fails
+    /// to parse.
+    Inspect {
+        gif_path: PathBuf,
+
+        #[arg(long, value_enum, default_value_t = Format::Human)]
+        format: Format,
+    },
+
+    /// Writes composited frames to image files.
+    Export {
+        gif_path: PathBuf,
+
+        /// Directory the frames are written to.
+        #[arg(short, long, default_value = ".")]
+        output_dir: PathBuf,
+
+        #[arg(long, value_enum, default_value_t = ImageFormat::Ppm)]
+        format: ImageFormat,
+
+        /// Frames to export, as `n`, `a..b`, `a..=b`, `a..` or `..b`. Exports
+        /// every frame by default.
+        #[arg(long, value_parser = parse_frame_range)]
+        frames: Option<FrameRange>,
+
+        /// File name of each frame. `{stem}` is the name of the GIF, `{ext}`
+        /// the extension of the format, and `{index}` the frame index, which
+        /// can be zero-padded as `{index:04}`. `{index}` is required when
+        /// more than one frame is exported.
+        #[arg(long, default_value = "{stem}-{index:04}.{ext}")]
+        template: String,
+
+        /// Skips writing `<stem>.json`, which lists every exported file and
+        /// its delay.
+        #[arg(long)]
+        no_manifest: bool,
+
+        /// Leaves pixels that no frame has painted transparent instead of
+        /// filling them with the background color.
+        #[arg(long)]
+        transparent_background: bool,
+
+        /// Grows the frames to fit the first one when it extends past the
+        /// logical screen, as browsers do, instead of clipping it.
+        #[arg(long)]
+        grow_canvas: bool,
+
+        /// How frame delays in the manifest are interpreted: `raw`, `browser`,
+        /// or a minimum delay in milliseconds.
+        #[arg(long, default_value = "browser")]
+        delay_policy: DelayPolicy,
+    },
+
+    /// Tiles every composited frame into a single image.
+    Sheet {
+        gif_path: PathBuf,
+
+        /// File the sheet is written to.
+        #[arg(short, long)]
+        output: PathBuf,
+
+        #[arg(long, value_enum, default_value_t = ImageFormat::Ppm)]
+        format: ImageFormat,
+
+        /// Number of frames per row. Defaults to a roughly square grid.
+        #[arg(long)]
+        columns: Option<usize>,
+
+        /// Space, in pixels, between frames and around the edge of the sheet.
+        #[arg(long, default_value_t = 0)]
+        spacing: usize,
+
+        /// Factor every frame is resized by.
+        #[arg(long, default_value_t = 1.0)]
+        scale: f32,
+
+        /// Color of the space between frames, as `RRGGBB`.
+        #[arg(long, default_value = "000000", value_parser = jif::flags::parse_color)]
+        background: u32,
+
+        /// Writes the index and delay of each frame underneath it.
+        #[arg(long)]
+        annotate: bool,
+
+        /// Writes a JSON atlas of each frame's rectangle to this file.
+        #[arg(long)]
+        atlas: Option<PathBuf>,
+
+        /// How annotated and atlas delays are interpreted: `raw`, `browser`,
+        /// or a minimum delay in milliseconds.
+        #[arg(long, default_value = "browser")]
+        delay_policy: DelayPolicy,
+    },
+
+    /// Reports every deviation from the GIF89a grammar, and fails if any GIF
+    /// has errors.
+    Validate {
+        #[arg(required = true)]
+        gif_paths: Vec<PathBuf>,
+
+        #[arg(long, value_enum, default_value_t = Format::Human)]
+        format: Format,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Human,
+    Json,
+}
+
+fn main() -> Result<()> {
+    let Args { command } = Args::parse();
+
+    match command {
+        Command::Inspect { gif_path, format } => inspect::run(&gif_path, format),
+        Command::Export {
+            gif_path,
+            output_dir,
+            format,
+            frames,
+            template,
+            no_manifest,
+            transparent_background,
+            grow_canvas,
+            delay_policy,
+        } => export::run(
+            &parse(&gif_path)?,
+            export::Options {
+                gif_path,
+                output_dir,
+                format,
+                frames,
+                template,
+                manifest: !no_manifest,
+                transparent_background,
+                grow_canvas,
+                delay_policy,
+            },
+        ),
+        Command::Sheet {
+            gif_path,
+            output,
+            format,
+            columns,
+            spacing,
+            scale,
+            background,
+            annotate,
+            atlas,
+            delay_policy,
+        } => sheet::run(
+            &parse(&gif_path)?,
+            sheet::Options {
+                output,
+                format,
+                atlas,
+                sheet: jif::SheetOptions {
+                    columns,
+                    spacing,
+                    scale,
+                    background,
+                    annotate,
+                    delay_policy,
+                },
+            },
+        ),
+        Command::Validate { gif_paths, format } => validate::run(&gif_paths, format),
+    }
+}
+
+fn parse(gif_path: &PathBuf) -> Result<GifDataStream> {
+    let data = std::fs::read(gif_path)?;
+    Ok(Decoder::new(data).parse()?)
+}
@@ -0,0 +1,114 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use eyre::{eyre, Result};
+use serde_json::json;
+
+use jif::gif_data_stream::GifDataStream;
+use jif::{Sheet, SheetOptions};
+
+use crate::export::ImageFormat;
+
+pub struct Options {
+    pub output: PathBuf,
+    pub format: ImageFormat,
+    pub atlas: Option<PathBuf>,
+    pub sheet: SheetOptions,
+}
+
+pub fn run(gif: &GifDataStream, options: Options) -> Result<()> {
+    let Options {
+        output,
+        format,
+        atlas,
+        sheet: sheet_options,
+    } = options;
+
+    let sheet = gif.contact_sheet(&sheet_options)?;
+    let (width, height) = (dimension(sheet.width)?, dimension(sheet.height)?);
+
+    let pixel_format = format.pixel_format();
+    let mut pixels = vec![0; sheet.pixels.len() * pixel_format.bytes_per_pixel()];
+    sheet.write_pixels(pixel_format, &mut pixels)?;
+
+    let mut out = BufWriter::new(File::create(&output)?);
+    format.write(&mut out, width, height, &pixels)?;
+    out.flush()?;
+
+    if let Some(atlas) = atlas {
+        let mut out = BufWriter::new(File::create(atlas)?);
+        serde_json::to_writer_pretty(&mut out, &to_atlas(&sheet, &output, sheet_options.scale))?;
+        writeln!(out)?;
+        out.flush()?;
+    }
+
+    println!(
+        "Wrote {} frames as a {}x{} sheet to {}.",
+        sheet.frames.len(),
+        width,
+        height,
+        output.display()
+    );
+
+    Ok(())
+}
+
+fn dimension(len: usize) -> Result<u32> {
+    u32::try_from(len).map_err(|_| eyre!("Contact sheet is too large."))
+}
+
+// the JSON array format read by most sprite sheet importers
+fn to_atlas(sheet: &Sheet, image: &Path, scale: f32) -> serde_json::Value {
+    let frames = sheet
+        .frames
+        .iter()
+        .map(|frame| {
+            json!({
+                "filename": frame.index.to_string(),
+                "frame": {
+                    "x": frame.x,
+                    "y": frame.y,
+                    "w": frame.width,
+                    "h": frame.height,
+                },
+                "duration": frame.delay.as_millis() as u64,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "frames": frames,
+        "meta": {
+            "image": image.file_name().map(|name| name.to_string_lossy()),
+            "size": { "w": sheet.width, "h": sheet.height },
+            "scale": scale,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn atlas() -> Result<()> {
+        let gif =
+            jif::Decoder::new(jif::dump_gif("../sample_gifs/sample_2_animation.gif")?).parse()?;
+        let options = SheetOptions {
+            columns: Some(2),
+            spacing: 1,
+            ..SheetOptions::default()
+        };
+
+        let sheet = gif.contact_sheet(&options)?;
+        let atlas = to_atlas(&sheet, Path::new("out/sheet.ppm"), 1.0);
+
+        assert_eq!(atlas["meta"]["image"], "sheet.ppm");
+        assert_eq!(atlas["frames"].as_array().unwrap().len(), 3);
+        assert_eq!(atlas["frames"][1]["frame"]["x"], 1 + 11 + 1);
+        assert_eq!(atlas["frames"][2]["frame"]["y"], 1 + 29 + 1);
+
+        Ok(())
+    }
+}
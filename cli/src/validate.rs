@@ -0,0 +1,151 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use eyre::{eyre, Result};
+use serde_json::{json, Value};
+
+use jif::{Diagnostic, Severity};
+
+use crate::Format;
+
+/// The diagnostics of one GIF, or why it couldn't be read.
+struct Report<'a> {
+    path: &'a Path,
+    diagnostics: io::Result<Vec<Diagnostic>>,
+}
+
+impl Report<'_> {
+    fn is_valid(&self) -> bool {
+        self.diagnostics.as_ref().is_ok_and(|diagnostics| {
+            diagnostics
+                .iter()
+                .all(|diagnostic| diagnostic.severity == Severity::Warning)
+        })
+    }
+}
+
+/// Validates every GIF in `gif_paths`, failing if any of them has errors.
+/// Warnings alone don't fail validation.
+pub fn run(gif_paths: &[PathBuf], format: Format) -> Result<()> {
+    let reports = gif_paths
+        .iter()
+        .map(|path| Report {
+            path,
+            diagnostics: std::fs::read(path).map(|data| jif::validate(&data)),
+        })
+        .collect::<Vec<_>>();
+
+    let mut out = io::stdout().lock();
+
+    match format {
+        Format::Human => write_human(&reports, &mut out)?,
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut out, &to_json(&reports))?;
+            writeln!(out)?;
+        }
+    }
+
+    let invalid = reports.iter().filter(|report| !report.is_valid()).count();
+
+    match invalid {
+        0 => Ok(()),
+        _ => Err(eyre!("{} of {} GIFs are invalid.", invalid, reports.len())),
+    }
+}
+
+fn write_human(reports: &[Report], out: &mut impl Write) -> io::Result<()> {
+    for report in reports {
+        let path = report.path.display();
+
+        match &report.diagnostics {
+            Ok(diagnostics) if diagnostics.is_empty() => writeln!(out, "{}: ok", path)?,
+            Ok(diagnostics) => {
+                for diagnostic in diagnostics {
+                    writeln!(out, "{}: {}", path, diagnostic)?;
+                }
+            }
+            Err(err) => writeln!(out, "{}: {}", path, err)?,
+        }
+    }
+
+    Ok(())
+}
+
+fn to_json(reports: &[Report]) -> Value {
+    let reports = reports
+        .iter()
+        .map(|report| {
+            let mut value = json!({
+                "path": report.path.display().to_string(),
+                "valid": report.is_valid(),
+            });
+
+            match &report.diagnostics {
+                Ok(diagnostics) => {
+                    value["diagnostics"] = diagnostics.iter().map(diagnostic_to_json).collect()
+                }
+                Err(err) => value["error"] = json!(err.to_string()),
+            }
+
+            value
+        })
+        .collect::<Vec<_>>();
+
+    json!(reports)
+}
+
+pub fn diagnostic_to_json(diagnostic: &Diagnostic) -> Value {
+    json!({
+        "severity": diagnostic.severity.to_string(),
+        "offset": diagnostic.offset,
+        "message": diagnostic.message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports() -> Result<()> {
+        let sample = Path::new("../sample_gifs/sample_1.gif");
+        let missing = Path::new("../sample_gifs/missing.gif");
+        let truncated = vec![Diagnostic::error(6, "The data stream ends in this block.")];
+
+        let reports = [
+            Report {
+                path: sample,
+                diagnostics: Ok(vec![]),
+            },
+            Report {
+                path: sample,
+                diagnostics: Ok(truncated),
+            },
+            Report {
+                path: missing,
+                diagnostics: std::fs::read(missing).map(|data| jif::validate(&data)),
+            },
+        ];
+
+        let mut human = vec![];
+        write_human(&reports, &mut human)?;
+        let human = String::from_utf8(human)?;
+        let lines = human.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "../sample_gifs/sample_1.gif: ok");
+        assert_eq!(
+            lines[1],
+            "../sample_gifs/sample_1.gif: error at 0x00000006: The data stream ends in this block."
+        );
+
+        let json = to_json(&reports);
+        assert_eq!(json[0]["valid"], true);
+        assert_eq!(json[1]["valid"], false);
+        assert_eq!(json[1]["diagnostics"][0]["offset"], 6);
+        assert_eq!(json[2]["valid"], false);
+        assert!(json[2]["error"].is_string());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+//! Benchmarks every GIF in `sample_gifs`, grouped by file size so that the
+//! large files don't drown out regressions in the small ones.
+//!
+//! Run with `cargo bench -p jif --features bench`.
+
+use std::fs;
+use std::hint::black_box;
+use std::path::Path;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use jif::bench::BitStream;
+use jif::gif_data_stream::{Block, GifDataStream};
+use jif::Decoder;
+
+const SAMPLE_GIFS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../sample_gifs");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SizeClass {
+    /// Under 16 KiB.
+    Small,
+
+    /// Under 1 MiB.
+    Medium,
+    Large,
+}
+
+impl SizeClass {
+    const fn of(len: usize) -> Self {
+        match len {
+            0..16_384 => Self::Small,
+            16_384..1_048_576 => Self::Medium,
+            _ => Self::Large,
+        }
+    }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Small => "small",
+            Self::Medium => "medium",
+            Self::Large => "large",
+        }
+    }
+}
+
+struct Sample {
+    name: String,
+    data: Vec<u8>,
+    gif: GifDataStream,
+}
+
+fn samples() -> Vec<Sample> {
+    let mut paths = fs::read_dir(SAMPLE_GIFS)
+        .expect("sample_gifs should be readable")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "gif"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    paths.iter().map(|path| sample(path)).collect()
+}
+
+fn sample(path: &Path) -> Sample {
+    let data = fs::read(path).unwrap();
+    let gif = Decoder::new(data.clone()).parse().unwrap();
+
+    Sample {
+        name: path.file_name().unwrap().to_string_lossy().into_owned(),
+        data,
+        gif,
+    }
+}
+
+// runs `bench` over every sample, in one group per size class
+fn bench_by_size(
+    c: &mut Criterion,
+    samples: &[Sample],
+    phase: &str,
+    throughput: impl Fn(&Sample) -> Throughput,
+    mut bench: impl FnMut(&mut criterion::Bencher, &Sample),
+) {
+    for class in [SizeClass::Small, SizeClass::Medium, SizeClass::Large] {
+        let mut group = c.benchmark_group(format!("{}/{}", phase, class.name()));
+
+        // a single decode of a large file takes hundreds of milliseconds
+        if class == SizeClass::Large {
+            group
+                .sample_size(10)
+                .measurement_time(Duration::from_secs(15));
+        }
+
+        for sample in samples
+            .iter()
+            .filter(|sample| SizeClass::of(sample.data.len()) == class)
+        {
+            group.throughput(throughput(sample));
+            group.bench_with_input(
+                BenchmarkId::from_parameter(&sample.name),
+                sample,
+                &mut bench,
+            );
+        }
+
+        group.finish();
+    }
+}
+
+fn frame_count(sample: &Sample) -> u64 {
+    sample
+        .gif
+        .blocks
+        .iter()
+        .filter(|block| matches!(block, Block::TableBasedImage(_)))
+        .count() as u64
+}
+
+fn parse(c: &mut Criterion, samples: &[Sample]) {
+    bench_by_size(
+        c,
+        samples,
+        "parse",
+        |sample| Throughput::Bytes(sample.data.len() as u64),
+        |b, sample| {
+            b.iter_batched(
+                || sample.data.clone(),
+                |data| Decoder::new(data).parse().unwrap(),
+                criterion::BatchSize::LargeInput,
+            )
+        },
+    );
+}
+
+fn bitstream(c: &mut Criterion, samples: &[Sample]) {
+    bench_by_size(
+        c,
+        samples,
+        "bitstream",
+        |sample| Throughput::Bytes(sample.data.len() as u64),
+        |b, sample| {
+            let images = sample
+                .gif
+                .blocks
+                .iter()
+                .filter_map(|block| match block {
+                    Block::TableBasedImage(tbi) => Some(tbi),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            let mut bitstream = BitStream::default();
+
+            // reads every image as codes of its starting code size
+            b.iter(|| {
+                let mut sum = 0;
+
+                for tbi in &images {
+                    let code_size = tbi.lzw_minimum_code as usize + 1;
+                    bitstream.load(tbi.image_data.iter().map(Vec::as_slice));
+
+                    while !bitstream.eof(code_size) {
+                        sum += bitstream.next(code_size).unwrap();
+                    }
+                }
+
+                black_box(sum)
+            })
+        },
+    );
+}
+
+fn lzw(c: &mut Criterion, samples: &[Sample]) {
+    bench_by_size(
+        c,
+        samples,
+        "lzw",
+        |sample| Throughput::Elements(frame_count(sample)),
+        |b, sample| {
+            let frame_decoder = sample.gif.frame_decoder().unwrap();
+            let frames = 0..frame_decoder.frames().len();
+            b.iter(|| {
+                frame_decoder
+                    .decompress_index_streams(frames.clone())
+                    .unwrap()
+            })
+        },
+    );
+}
+
+fn decompress(c: &mut Criterion, samples: &[Sample]) {
+    bench_by_size(
+        c,
+        samples,
+        "decompress",
+        |sample| Throughput::Elements(frame_count(sample)),
+        |b, sample| b.iter(|| sample.gif.decompress().unwrap()),
+    );
+}
+
+fn benches(c: &mut Criterion) {
+    let samples = samples();
+
+    parse(c, &samples);
+    bitstream(c, &samples);
+    lzw(c, &samples);
+    decompress(c, &samples);
+}
+
+criterion_group!(decode, benches);
+criterion_main!(decode);
@@ -1,6 +1,8 @@
-use eyre::{Ok, Result};
+use alloc::vec::Vec;
 
-#[derive(Debug)]
+use crate::error::Result;
+
+#[derive(Debug, Default)]
 pub struct BitStream {
     // in bits
     pub cursor: usize,
@@ -8,21 +10,39 @@ pub struct BitStream {
 }
 
 impl BitStream {
-    pub fn new(image_data: &Vec<Vec<u8>>) -> Self {
-        let mut data = vec![];
+    #[cfg(test)]
+    pub fn new(image_data: &[Vec<u8>]) -> Self {
+        let mut bitstream = Self::default();
+        bitstream.load(image_data.iter().map(Vec::as_slice));
+        bitstream
+    }
 
-        for image in image_data {
-            data.extend(image);
-        }
+    /// Replaces the contents of the bit stream with the given sub-blocks,
+    /// reusing its buffer.
+    pub fn load<'a>(&mut self, sub_blocks: impl IntoIterator<Item = &'a [u8]>) {
+        self.cursor = 0;
+        self.data.clear();
 
-        Self { cursor: 0, data }
+        for sub_block in sub_blocks {
+            self.data.extend(sub_block);
+        }
     }
 
     #[inline]
-    pub fn eof(&self, bit_length: usize) -> bool {
+    pub const fn eof(&self, bit_length: usize) -> bool {
         self.cursor + bit_length > self.data.len() * 8
     }
 
+    /// Whether any bit after the cursor is set.
+    pub fn has_set_bits(&self) -> bool {
+        let (byte_idx, bit_idx) = self.divmod_8();
+
+        match self.data.get(byte_idx..) {
+            Some([byte, rest @ ..]) => byte >> bit_idx != 0 || rest.iter().any(|byte| *byte != 0),
+            _ => false,
+        }
+    }
+
     #[inline]
     const fn divmod_8(&self) -> (usize, usize) {
         (self.cursor / 8, self.cursor % 8)
@@ -58,7 +78,7 @@ mod tests {
             0x8C, 0x2D, 0x99, 0x87, 0x2A, 0x1C, 0xDC, 0x33, 0xA0, 0x02, 0x75, 0xEC, 0x95, 0xFA,
             0xA8, 0xDE, 0x60, 0x8C, 0x04, 0x91, 0x4C, 0x01, 0x00,
         ];
-        let mut bitstream = BitStream::new(&vec![data.to_vec()]);
+        let mut bitstream = BitStream::new(&[data.to_vec()]);
 
         assert_eq!(bitstream.read_bit(), 0);
         assert_eq!(bitstream.read_bit(), 0);
@@ -74,7 +94,7 @@ mod tests {
     fn test_read_bytes() -> Result<()> {
         let data = [0x8c];
 
-        let mut bitstream = BitStream::new(&vec![data.to_vec()]);
+        let mut bitstream = BitStream::new(&[data.to_vec()]);
 
         assert_eq!(bitstream.next(3)?, 4);
         assert_eq!(bitstream.next(3)?, 1);
@@ -82,11 +102,27 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn has_set_bits() -> Result<()> {
+        let mut bitstream = BitStream::new(&[[0b0000_0111, 0].to_vec()]);
+
+        bitstream.next(2)?;
+        assert!(bitstream.has_set_bits());
+
+        bitstream.next(1)?;
+        assert!(!bitstream.has_set_bits());
+
+        bitstream.next(13)?;
+        assert!(!bitstream.has_set_bits());
+
+        Ok(())
+    }
+
     #[test]
     fn test_read_dance_header() -> Result<()> {
         let data = [0, 157];
 
-        let mut bitstream = BitStream::new(&vec![data.to_vec()]);
+        let mut bitstream = BitStream::new(&[data.to_vec()]);
 
         assert_eq!(bitstream.read_bit(), 0);
         assert_eq!(bitstream.read_bit(), 0);
@@ -98,7 +134,7 @@ mod tests {
         assert_eq!(bitstream.read_bit(), 0);
         assert_eq!(bitstream.read_bit(), 1);
 
-        bitstream = BitStream::new(&vec![data.to_vec()]);
+        bitstream = BitStream::new(&[data.to_vec()]);
         assert_eq!(bitstream.next(9)?, 256);
 
         Ok(())
@@ -1,5 +1,7 @@
-use eyre::{eyre, Ok, Result};
+use alloc::borrow::ToOwned;
+use alloc::vec::Vec;
 
+use crate::error::{error, Result};
 use crate::grammar::label::TRAILER;
 
 #[derive(Debug)]
@@ -13,7 +15,7 @@ impl Buffer {
         Self { cursor: 0, data }
     }
 
-    pub const fn _current(&self) -> usize {
+    pub const fn current(&self) -> usize {
         self.cursor
     }
 
@@ -39,7 +41,7 @@ impl Buffer {
         self.eof(N - 1)?;
 
         if bytes != self.data[self.cursor..self.cursor + N] {
-            return Err(eyre!(
+            return Err(error!(
                 "Unexpected slice {:?}, expected: {:?}",
                 &bytes,
                 &self.data[self.cursor..self.cursor + N]
@@ -57,7 +59,7 @@ impl Buffer {
             return Ok(());
         }
 
-        Err(eyre!("Unexpected EOF"))
+        Err(error!("Unexpected EOF"))
     }
 
     pub fn read_u16(&mut self) -> Result<u16> {
@@ -1,17 +1,31 @@
-use eyre::{eyre, Result};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
 
+use crate::diagnostic::Diagnostic;
+use crate::error::{error, Error, Result};
+use crate::gif_data_stream::{Block, GifDataStream};
+use crate::version::{Version, VersionPolicy};
 use crate::{
     buffer::Buffer,
     grammar::{
-        ApplicationExtension,
-        CommentExtension, GraphicControlExtension, ImageDescriptor, label::{
+        label::{
             APPLICATION_EXTENSION, COMMENT_EXTENSION, EXTENSION, GRAPHIC_CONTROL_EXTENSION,
             IMAGE_DESCRIPTOR, PLAIN_TEXT_EXTENSION,
         },
+        ApplicationExtension, CommentExtension, GraphicControlExtension, ImageDescriptor,
         LogicalScreenDescriptor, PlainTextExtension, TableBasedImage,
     },
 };
-use crate::gif_data_stream::{Block, GifDataStream};
+
+/// Byte offset of the version, right after the `GIF` signature.
+const VERSION_OFFSET: usize = 3;
+
+/// Reported for extension blocks in a GIF87a data stream, by the decoder and
+/// the validator alike.
+pub const GIF87A_EXTENSION: &str =
+    "Extension blocks were introduced in GIF89a, but the data stream is GIF87a.";
 
 /// The decoder is the program used to process a GIF data stream.
 ///
@@ -21,19 +35,68 @@ use crate::gif_data_stream::{Block, GifDataStream};
 #[derive(Debug)]
 pub struct Decoder {
     buffer: Buffer,
+    version_policy: VersionPolicy,
+    max_canvas_area: Option<usize>,
 }
 
 impl Decoder {
     pub const fn new(data: Vec<u8>) -> Self {
         Self {
             buffer: Buffer::new(data),
+            version_policy: VersionPolicy::Warn,
+            max_canvas_area: None,
         }
     }
 
+    /// Sets what happens when the data stream doesn't follow the version it
+    /// claims. Defaults to [`VersionPolicy::Warn`].
+    pub const fn with_version_policy(mut self, version_policy: VersionPolicy) -> Self {
+        self.version_policy = version_policy;
+        self
+    }
+
+    /// Fails to parse data streams whose logical screen, or any of whose
+    /// images, has more than `max_canvas_area` pixels. A canvas is allocated
+    /// for the logical screen before any frame is decoded, so this bounds the
+    /// memory used to decode untrusted data streams. Unlimited by default.
+    pub const fn with_max_canvas_area(mut self, max_canvas_area: usize) -> Self {
+        self.max_canvas_area = Some(max_canvas_area);
+        self
+    }
+
+    /// Reads an entire GIF data stream from `reader`.
+    #[cfg(feature = "std")]
+    pub fn from_reader(mut reader: impl std::io::Read) -> Result<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Ok(Self::new(data))
+    }
+
     pub fn parse(&mut self) -> Result<GifDataStream> {
+        match self.parse_partial()? {
+            (gif, None) => Ok(gif),
+            (_, Some(diagnostic)) => Err(Error::new(diagnostic.message)),
+        }
+    }
+
+    /// Parses the data stream up to the first block that fails to parse.
+    ///
+    /// Returns the blocks before it, along with an error diagnostic at the
+    /// offset of the block that failed, so that broken data streams can still
+    /// be inspected. Fails outright if the header or the logical screen can't
+    /// be parsed.
+    pub fn parse_partial(&mut self) -> Result<(GifDataStream, Option<Diagnostic>)> {
+        let version_policy = self.version_policy;
         let buffer = &mut self.buffer;
+        let mut diagnostics = vec![];
+
         buffer.expect(*b"GIF")?;
-        let version = String::from_utf8(buffer.read_slice(3)?)?;
+        let version = Version::from_bytes([buffer.next()?, buffer.next()?, buffer.next()?]);
+
+        if let Version::Unknown(_) = version {
+            let message = format!("Unknown version GIF{}.", version);
+            deviate(version_policy, &mut diagnostics, VERSION_OFFSET, message)?;
+        }
 
         // logical_screen_descriptor
         let logical_screen_descriptor = LogicalScreenDescriptor {
@@ -44,138 +107,195 @@ impl Decoder {
             pixel_aspect_ratio: buffer.next()?,
         };
 
+        check_area(
+            "Logical screen",
+            logical_screen_descriptor.canvas_width,
+            logical_screen_descriptor.canvas_height,
+            self.max_canvas_area,
+        )?;
+
         let global_color_table = if logical_screen_descriptor.global_color_table_flag() {
             let global_color_table_size = logical_screen_descriptor.global_color_table_size();
 
-            let buffer = buffer.read_slice(global_color_table_size)?;
-
-            Some(buffer)
+            Some(self.buffer.read_slice(global_color_table_size)?)
         } else {
             None
         };
 
         let mut blocks = vec![];
+        let mut block_offsets = vec![];
+        let mut error = None;
 
         // this loop iterates by every <Data> block
-        while !buffer.at_end() {
-            let byte = buffer.next()?;
-
-            if byte == EXTENSION {
-                match buffer.next()? {
-                    APPLICATION_EXTENSION => {
-                        let _block_size = buffer.next()? as usize;
-                        let application_extension = ApplicationExtension {
-                            identifier: String::from_utf8(buffer.read_slice(8)?)?,
-                            authentication_code: [buffer.next()?, buffer.next()?, buffer.next()?],
-                            data: {
-                                let data_size = buffer.next()? as usize;
-                                buffer.read_slice(data_size)?
-                            },
-                        };
-
-                        buffer.next()?;
-                        blocks.push(Block::ApplicationExtension(application_extension));
-                    }
-                    COMMENT_EXTENSION => {
-                        let block_size = buffer.next()?;
+        while !self.buffer.at_end() {
+            let offset = self.buffer.current();
 
-                        let comment_extension = CommentExtension {
-                            data: buffer.read_slice(block_size as usize)?,
-                        };
-
-                        let _term_byte = buffer.next()?;
+            match self.parse_block(version, global_color_table.is_some(), &mut diagnostics) {
+                Ok(Some(block)) => {
+                    blocks.push(block);
+                    block_offsets.push(offset);
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    error = Some(Diagnostic::error(offset, err.message()));
+                    break;
+                }
+            }
+        }
 
-                        blocks.push(Block::CommentExtension(comment_extension));
-                    }
-                    GRAPHIC_CONTROL_EXTENSION => {
-                        let _block_size = buffer.next()?;
-                        let graphic_control_extension = GraphicControlExtension {
-                            packed_field: buffer.next()?,
-                            delay_time: buffer.read_u16()?,
-                            transparent_color_index: buffer.next()?,
-                        };
+        let gif = GifDataStream {
+            version,
+            logical_screen_descriptor,
+            global_color_table,
+            blocks,
+            block_offsets,
+            diagnostics,
+        };
 
-                        let _term_byte = buffer.next()?;
+        Ok((gif, error))
+    }
 
-                        blocks.push(Block::GraphicControlExtension(graphic_control_extension));
-                    }
-                    PLAIN_TEXT_EXTENSION => {
-                        if global_color_table.as_ref().is_none() {
-                            return Err(eyre!(
-                                "This block requires a Global Color Table to be available."
-                            ));
-                        }
+    // parses the block starting at the current byte, skipping bytes that don't
+    // start one
+    fn parse_block(
+        &mut self,
+        version: Version,
+        has_global_color_table: bool,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Result<Option<Block>> {
+        let buffer = &mut self.buffer;
+        let offset = buffer.current();
+        let byte = buffer.next()?;
+        let block;
+
+        if byte == EXTENSION {
+            if version == Version::Gif87a {
+                deviate(
+                    self.version_policy,
+                    diagnostics,
+                    offset,
+                    GIF87A_EXTENSION.into(),
+                )?;
+            }
 
-                        let block_size = {
-                            let size = buffer.next()?;
-
-                            if size < 12 {
-                                return Err(eyre!("Invalid Plain Text Extension, block must be at least 12 bytes long."));
-                            }
-
-                            size as usize
-                        };
-
-                        let plain_text_extension = PlainTextExtension {
-                            text_grid_left_position: buffer.read_u16()?,
-                            text_grid_top_position: buffer.read_u16()?,
-                            text_grid_width: buffer.read_u16()?,
-                            text_grid_height: buffer.read_u16()?,
-                            character_cell_width: buffer.next()?,
-                            character_cell_height: buffer.next()?,
-                            text_foreground_color_index: buffer.next()?,
-                            text_background_color_index: buffer.next()?,
-                            plain_text_data: buffer.read_slice(block_size)?,
-                        };
-
-                        let _term_byte = buffer.next()?;
-                        blocks.push(Block::PlainTextExtension(plain_text_extension));
-                    }
-                    _ => return Err(eyre!("Encountered an inner block extension")),
+            match buffer.next()? {
+                APPLICATION_EXTENSION => {
+                    let _block_size = buffer.next()? as usize;
+                    let application_extension = ApplicationExtension {
+                        identifier: String::from_utf8(buffer.read_slice(8)?)?,
+                        authentication_code: [buffer.next()?, buffer.next()?, buffer.next()?],
+                        data: {
+                            let data_size = buffer.next()? as usize;
+                            buffer.read_slice(data_size)?
+                        },
+                    };
+
+                    buffer.next()?;
+                    block = Some(Block::ApplicationExtension(application_extension));
                 }
-            } else if byte == IMAGE_DESCRIPTOR {
-                let image_descriptor = ImageDescriptor {
-                    image_left: buffer.read_u16()?,
-                    image_top: buffer.read_u16()?,
-                    image_width: buffer.read_u16()?,
-                    image_height: buffer.read_u16()?,
-                    packed_field: buffer.next()?,
-                };
+                COMMENT_EXTENSION => {
+                    let block_size = buffer.next()?;
 
-                let local_color_table = if image_descriptor.local_color_table_flag() {
-                    let local_color_table_size = image_descriptor.local_color_table_size();
+                    let comment_extension = CommentExtension {
+                        data: buffer.read_slice(block_size as usize)?,
+                    };
 
-                    Some(buffer.read_slice(local_color_table_size)?)
-                } else {
-                    None
-                };
+                    let _term_byte = buffer.next()?;
 
-                let lzw_minimum_code = buffer.next()?;
-
-                let mut sub_blocks = vec![];
+                    block = Some(Block::CommentExtension(comment_extension));
+                }
+                GRAPHIC_CONTROL_EXTENSION => {
+                    let _block_size = buffer.next()?;
+                    let graphic_control_extension = GraphicControlExtension {
+                        packed_field: buffer.next()?,
+                        delay_time: buffer.read_u16()?,
+                        transparent_color_index: buffer.next()?,
+                    };
 
-                let mut block_size = buffer.next()?;
+                    let _term_byte = buffer.next()?;
 
-                while block_size != 0 {
-                    sub_blocks.push(buffer.read_slice(block_size as usize)?);
-                    block_size = buffer.next()?;
+                    block = Some(Block::GraphicControlExtension(graphic_control_extension));
                 }
+                PLAIN_TEXT_EXTENSION => {
+                    if !has_global_color_table {
+                        return Err(error!(
+                            "This block requires a Global Color Table to be available."
+                        ));
+                    }
+
+                    let block_size = {
+                        let size = buffer.next()?;
+
+                        if size < 12 {
+                            return Err(error!("Invalid Plain Text Extension, block must be at least 12 bytes long."));
+                        }
 
-                blocks.push(Block::TableBasedImage(TableBasedImage {
-                    image_descriptor,
-                    local_color_table,
-                    lzw_minimum_code,
-                    image_data: sub_blocks,
-                }));
+                        size as usize
+                    };
+
+                    let plain_text_extension = PlainTextExtension {
+                        text_grid_left_position: buffer.read_u16()?,
+                        text_grid_top_position: buffer.read_u16()?,
+                        text_grid_width: buffer.read_u16()?,
+                        text_grid_height: buffer.read_u16()?,
+                        character_cell_width: buffer.next()?,
+                        character_cell_height: buffer.next()?,
+                        text_foreground_color_index: buffer.next()?,
+                        text_background_color_index: buffer.next()?,
+                        plain_text_data: buffer.read_slice(block_size)?,
+                    };
+
+                    let _term_byte = buffer.next()?;
+                    block = Some(Block::PlainTextExtension(plain_text_extension));
+                }
+                _ => return Err(error!("Encountered an inner block extension")),
+            }
+        } else if byte == IMAGE_DESCRIPTOR {
+            let image_descriptor = ImageDescriptor {
+                image_left: buffer.read_u16()?,
+                image_top: buffer.read_u16()?,
+                image_width: buffer.read_u16()?,
+                image_height: buffer.read_u16()?,
+                packed_field: buffer.next()?,
+            };
+
+            check_area(
+                "Image",
+                image_descriptor.image_width,
+                image_descriptor.image_height,
+                self.max_canvas_area,
+            )?;
+
+            let local_color_table = if image_descriptor.local_color_table_flag() {
+                let local_color_table_size = image_descriptor.local_color_table_size();
+
+                Some(buffer.read_slice(local_color_table_size)?)
+            } else {
+                None
+            };
+
+            let lzw_minimum_code = buffer.next()?;
+
+            let mut sub_blocks = vec![];
+
+            let mut block_size = buffer.next()?;
+
+            while block_size != 0 {
+                sub_blocks.push(buffer.read_slice(block_size as usize)?);
+                block_size = buffer.next()?;
             }
+
+            block = Some(Block::TableBasedImage(TableBasedImage {
+                image_descriptor,
+                local_color_table,
+                lzw_minimum_code,
+                image_data: sub_blocks,
+            }));
+        } else {
+            block = None;
         }
 
-        Ok(GifDataStream {
-            version,
-            logical_screen_descriptor,
-            global_color_table,
-            blocks,
-        })
+        Ok(block)
     }
 
     pub fn decode(&mut self) -> Result<()> {
@@ -185,9 +305,38 @@ impl Decoder {
     }
 }
 
-#[cfg(test)]
+fn check_area(name: &str, width: u16, height: u16, max_area: Option<usize>) -> Result<()> {
+    match max_area {
+        Some(max_area) if width as usize * height as usize > max_area => Err(error!(
+            "{} of {}x{} pixels is larger than the maximum area of {} pixels.",
+            name, width, height, max_area
+        )),
+        _ => Ok(()),
+    }
+}
+
+// handles a data stream straying from the version it claims, according to the
+// version policy
+fn deviate(
+    version_policy: VersionPolicy,
+    diagnostics: &mut Vec<Diagnostic>,
+    offset: usize,
+    message: String,
+) -> Result<()> {
+    match version_policy {
+        VersionPolicy::Reject => Err(Error::new(message)),
+        VersionPolicy::Warn => {
+            diagnostics.push(Diagnostic::warning(offset, message));
+            Ok(())
+        }
+        VersionPolicy::Accept => Ok(()),
+    }
+}
+
+// every test reads a sample GIF from disk
+#[cfg(all(test, feature = "std"))]
 mod tests {
-    use crate::dump_gif;
+    use crate::{dump_gif, Severity};
 
     use super::*;
 
@@ -225,4 +374,105 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn loop_count() -> Result<()> {
+        let looping = Decoder::new(dump_gif("../sample_gifs/sample_2_animation.gif")?).parse()?;
+        let still = Decoder::new(dump_gif("../sample_gifs/sample_1.gif")?).parse()?;
+
+        assert_eq!(looping.loop_count(), Some(0));
+        assert_eq!(still.loop_count(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_partial() -> Result<()> {
+        let data = dump_gif("../sample_gifs/sample_2_animation.gif")?;
+        let gif = Decoder::new(data.clone()).parse()?;
+        let last = *gif.block_offsets.last().unwrap();
+
+        // cut off in the middle of the last block
+        let (partial, error) = Decoder::new(data[..last + 3].to_vec()).parse_partial()?;
+        let error = error.unwrap();
+
+        assert_eq!(
+            partial.block_offsets,
+            gif.block_offsets[..gif.blocks.len() - 1]
+        );
+        assert_eq!(error.severity, Severity::Error);
+        assert_eq!(error.offset, last);
+
+        let (complete, error) = Decoder::new(data).parse_partial()?;
+        assert_eq!(complete.block_offsets, gif.block_offsets);
+        assert!(error.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn max_canvas_area() -> Result<()> {
+        let data = dump_gif("../sample_gifs/sample_2_animation.gif")?;
+        let area = 11 * 29;
+
+        Decoder::new(data.clone())
+            .with_max_canvas_area(area)
+            .parse()?;
+        assert!(Decoder::new(data)
+            .with_max_canvas_area(area - 1)
+            .parse()
+            .is_err());
+
+        // a header alone can declare a canvas of 16 GiB
+        let mut header = b"GIF89a".to_vec();
+        header.extend([0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0]);
+        let err = Decoder::new(header)
+            .with_max_canvas_area(1 << 24)
+            .parse()
+            .unwrap_err();
+        assert!(err.message().contains("65535x65535"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn version_policy() -> Result<()> {
+        let mut data = dump_gif("../sample_gifs/sample_2_animation.gif")?;
+        data[3..6].copy_from_slice(b"87a");
+
+        let gif = Decoder::new(data.clone()).parse()?;
+        assert_eq!(gif.version, Version::Gif87a);
+        let extension_offsets = gif
+            .blocks
+            .iter()
+            .zip(&gif.block_offsets)
+            .filter(|(block, _)| !matches!(block, Block::TableBasedImage(_)))
+            .map(|(_, offset)| *offset)
+            .collect::<Vec<_>>();
+        let diagnostic_offsets = gif
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.offset)
+            .collect::<Vec<_>>();
+
+        assert!(!extension_offsets.is_empty());
+        assert_eq!(diagnostic_offsets, extension_offsets);
+
+        let accepted = Decoder::new(data.clone())
+            .with_version_policy(VersionPolicy::Accept)
+            .parse()?;
+        assert!(accepted.diagnostics.is_empty());
+
+        let rejected = Decoder::new(data.clone())
+            .with_version_policy(VersionPolicy::Reject)
+            .parse();
+        assert!(rejected.is_err());
+
+        data[3..6].copy_from_slice(b"12x");
+        let gif = Decoder::new(data).parse()?;
+        assert_eq!(gif.version, Version::Unknown(*b"12x"));
+        assert_eq!(gif.diagnostics[0].offset, VERSION_OFFSET);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,117 @@
+use core::str::FromStr;
+use core::time::Duration;
+
+use crate::error::{error, Error};
+
+/// Delays at or below this many hundredths of a second are treated as
+/// "unspecified" by browsers.
+const BROWSER_MIN_DELAY_TIME: u16 = 1;
+
+/// The delay browsers substitute for unspecified delays.
+const BROWSER_DEFAULT_DELAY: Duration = Duration::from_millis(100);
+
+/// A DelayPolicy turns the delay time of a frame into the duration it should be
+/// displayed for.
+///
+/// The GIF89a specification leaves the meaning of a 0 delay time, or of a frame
+/// without a GraphicControlExtension, up to the decoder. Most encoders rely on
+/// browsers clamping these to 100 ms, so playing them back raw is usually far
+/// too fast.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DelayPolicy {
+    /// Use the delay time as it appears in the data stream. Frames without a
+    /// delay time are not delayed at all.
+    Raw,
+
+    /// Match browsers, which display frames with a delay time of 0 or 1, or
+    /// with no delay time at all, for 100 ms.
+    #[default]
+    BrowserCompatible,
+
+    /// Display every frame for at least the given duration.
+    Minimum(Duration),
+}
+
+impl DelayPolicy {
+    /// Computes the display duration of a frame from its delay time, given in
+    /// hundredths (1/100) of a second.
+    pub fn duration(&self, delay_time: Option<u16>) -> Duration {
+        let raw = Duration::from_millis(delay_time.unwrap_or(0) as u64 * 10);
+
+        match self {
+            Self::Raw => raw,
+            Self::BrowserCompatible => match delay_time {
+                Some(delay_time) if delay_time > BROWSER_MIN_DELAY_TIME => raw,
+                _ => BROWSER_DEFAULT_DELAY,
+            },
+            Self::Minimum(minimum) => raw.max(*minimum),
+        }
+    }
+}
+
+/// Parses `raw`, `browser`, or a minimum duration in milliseconds.
+impl FromStr for DelayPolicy {
+    type Err = Error;
+
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        match policy {
+            "raw" => Ok(Self::Raw),
+            "browser" => Ok(Self::BrowserCompatible),
+            millis => millis
+                .parse()
+                .map(|millis| Self::Minimum(Duration::from_millis(millis)))
+                .map_err(|_| {
+                    error!(
+                        "expected `raw`, `browser` or milliseconds, got `{}`",
+                        millis
+                    )
+                }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw() {
+        assert_eq!(DelayPolicy::Raw.duration(None), Duration::ZERO);
+        assert_eq!(DelayPolicy::Raw.duration(Some(0)), Duration::ZERO);
+        assert_eq!(
+            DelayPolicy::Raw.duration(Some(7)),
+            Duration::from_millis(70)
+        );
+    }
+
+    #[test]
+    fn browser_compatible() {
+        let policy = DelayPolicy::BrowserCompatible;
+
+        assert_eq!(policy.duration(None), Duration::from_millis(100));
+        assert_eq!(policy.duration(Some(0)), Duration::from_millis(100));
+        assert_eq!(policy.duration(Some(1)), Duration::from_millis(100));
+        assert_eq!(policy.duration(Some(2)), Duration::from_millis(20));
+        assert_eq!(policy.duration(Some(50)), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn minimum() {
+        let policy = DelayPolicy::Minimum(Duration::from_millis(50));
+
+        assert_eq!(policy.duration(None), Duration::from_millis(50));
+        assert_eq!(policy.duration(Some(2)), Duration::from_millis(50));
+        assert_eq!(policy.duration(Some(20)), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!("raw".parse(), Ok(DelayPolicy::Raw));
+        assert_eq!("browser".parse(), Ok(DelayPolicy::BrowserCompatible));
+        assert_eq!(
+            "50".parse(),
+            Ok(DelayPolicy::Minimum(Duration::from_millis(50)))
+        );
+        assert!("fast".parse::<DelayPolicy>().is_err());
+    }
+}
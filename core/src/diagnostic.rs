@@ -0,0 +1,61 @@
+use alloc::string::String;
+use core::fmt;
+
+/// How far a data stream strays from the GIF specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The data stream breaks the specification, but can still be decoded.
+    Warning,
+
+    /// The data stream can not be decoded as intended.
+    Error,
+}
+
+/// A deviation from the GIF specification found in a data stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+
+    /// Byte offset, from the start of the data stream, of the block the
+    /// deviation was found in.
+    pub offset: usize,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn warning(offset: usize, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            offset,
+            message: message.into(),
+        }
+    }
+
+    pub fn error(offset: usize, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            offset,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => f.write_str("warning"),
+            Self::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// Writes the diagnostic as `<severity> at <offset>: <message>`.
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {:#010x}: {}",
+            self.severity, self.offset, self.message
+        )
+    }
+}
@@ -0,0 +1,63 @@
+use alloc::string::{FromUtf8Error, String, ToString};
+use core::fmt;
+
+/// An error encountered while parsing or decoding a GIF data stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    message: String,
+}
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
+
+impl Error {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl core::error::Error for Error {}
+
+impl From<FromUtf8Error> for Error {
+    fn from(err: FromUtf8Error) -> Self {
+        Self::new(err.to_string())
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::new(err.to_string())
+    }
+}
+
+/// Builds an [`Error`] from a format string.
+macro_rules! error {
+    ($($arg:tt)*) => {
+        $crate::error::Error::new(alloc::format!($($arg)*))
+    };
+}
+
+pub(crate) use error;
+
+/// Converts an [`Option`] into a [`Result`] with the given error message.
+pub trait OptionExt<T> {
+    fn ok_or_error(self, message: &'static str) -> Result<T>;
+}
+
+impl<T> OptionExt<T> for Option<T> {
+    fn ok_or_error(self, message: &'static str) -> Result<T> {
+        self.ok_or_else(|| Error::new(message))
+    }
+}
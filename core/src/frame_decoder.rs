@@ -0,0 +1,981 @@
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::ops::Range;
+
+use crate::diagnostic::Diagnostic;
+use crate::error::{error, OptionExt, Result};
+use crate::gif_data_stream::{Block, GifDataStream};
+use crate::grammar::{
+    parse_color_table, parse_color_table_into, DisposalMethod, Frame, FrameMeta,
+    GraphicControlExtension, ImageDescriptor, IndexedFrame, IndexedFrames, LogicalScreenDescriptor,
+    TableBasedImage, DEFAULT_BACKGROUND_COLOR, TRANSPARENT,
+};
+use crate::lzw::{self, LzwDecoder};
+use crate::pixel_format::PixelFormat;
+
+/// Where a frame lives in the data stream, and what is needed to composite it.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameInfo {
+    /// Index of the frame's TableBasedImage in [`GifDataStream::blocks`].
+    pub block: usize,
+
+    /// Index of the GraphicControlExtension that applies to this frame, if any.
+    pub graphic_control_extension: Option<usize>,
+
+    /// Byte offset of the frame's ImageDescriptor in the data stream.
+    pub offset: usize,
+
+    /// Whether the frame can be composited without decoding any of the frames
+    /// before it.
+    ///
+    /// This is the case when the canvas is known to be cleared to the
+    /// background color before the frame is drawn, or when the frame covers
+    /// the entire canvas with opaque pixels.
+    pub keyframe: bool,
+
+    disposal_method: DisposalMethod,
+}
+
+impl FrameInfo {
+    /// The TableBasedImage of this frame.
+    pub fn table_based_image<'a>(&self, gif: &'a GifDataStream) -> Result<&'a TableBasedImage> {
+        match &gif.blocks[self.block] {
+            Block::TableBasedImage(tbi) => Ok(tbi),
+            _ => Err(error!(
+                "Expected block {} to be a TableBasedImage.",
+                self.block
+            )),
+        }
+    }
+
+    /// The GraphicControlExtension that applies to this frame, if any.
+    pub fn graphic_control_extension<'a>(
+        &self,
+        gif: &'a GifDataStream,
+    ) -> Result<Option<&'a GraphicControlExtension>> {
+        self.graphic_control_extension
+            .map(|index| match &gif.blocks[index] {
+                Block::GraphicControlExtension(gce) => Ok(gce),
+                _ => Err(error!(
+                    "Expected block {} to be a GraphicControlExtension.",
+                    index
+                )),
+            })
+            .transpose()
+    }
+}
+
+/// The FrameDecoder composites the frames of a GIF data stream one at a time.
+///
+/// Unlike [`GifDataStream::decompress`], it only keeps a single canvas in
+/// memory, and it can [`seek`](FrameDecoder::seek) to any frame by compositing
+/// forward from the nearest keyframe rather than from the first frame.
+///
+/// The decoder either borrows the GifDataStream, as returned by
+/// [`GifDataStream::frame_decoder`], or owns it through any type that borrows
+/// as one, such as a `Box` or an `Rc`.
+#[derive(Debug)]
+pub struct FrameDecoder<G> {
+    gif: G,
+    frames: Vec<FrameInfo>,
+    global_color_table: Option<Vec<u32>>,
+    canvas: Canvas<u32>,
+
+    // index of the frame that will be composited next
+    cursor: usize,
+
+    // layout of the buffers written by `next_frame_into`
+    pixel_format: PixelFormat,
+    stride: Option<usize>,
+
+    // buffers reused between frames
+    lzw_decoder: LzwDecoder,
+    index_stream: Vec<u8>,
+    local_color_table: Vec<u32>,
+
+    diagnostics: Vec<Diagnostic>,
+
+    // offsets of the frames in `diagnostics`, as a frame is composited again
+    // after seeking but only reported once
+    reported: BTreeSet<usize>,
+}
+
+impl<G: Borrow<GifDataStream>> FrameDecoder<G> {
+    pub fn new(gif: G) -> Result<Self> {
+        let gif_data_stream = gif.borrow();
+
+        let LogicalScreenDescriptor {
+            canvas_width,
+            canvas_height,
+            background_color_index,
+            ..
+        } = gif_data_stream.logical_screen_descriptor;
+
+        let global_color_table = gif_data_stream
+            .global_color_table
+            .as_ref()
+            .map(|t| parse_color_table(t.as_slice()));
+
+        let background_color = match global_color_table.as_ref() {
+            Some(gct) => *gct
+                .get(background_color_index as usize)
+                .ok_or_error("Background color is out of bounds")?,
+            None => DEFAULT_BACKGROUND_COLOR,
+        };
+
+        let frames = index_frames(gif_data_stream, canvas_width, canvas_height)?;
+
+        Ok(Self {
+            gif,
+            frames,
+            global_color_table,
+            canvas: Canvas::new(canvas_width, canvas_height, background_color),
+            cursor: 0,
+            pixel_format: PixelFormat::default(),
+            stride: None,
+            lzw_decoder: LzwDecoder::default(),
+            index_stream: vec![],
+            local_color_table: vec![],
+            diagnostics: vec![],
+            reported: BTreeSet::new(),
+        })
+    }
+
+    /// Composites frames onto a transparent canvas instead of one filled with
+    /// the background color, as browsers do. Pixels that no frame has painted
+    /// are [`TRANSPARENT`].
+    pub fn with_transparent_background(mut self) -> Self {
+        self.canvas.background = TRANSPARENT;
+        self.canvas.clear();
+        self
+    }
+
+    /// Grows the canvas to fit the first frame when it extends past the
+    /// logical screen, as browsers do, instead of clipping it. Frames are then
+    /// the size of [`canvas_size`](FrameDecoder::canvas_size) rather than the
+    /// size of the logical screen.
+    pub fn with_grown_canvas(mut self) -> Result<Self> {
+        let gif = self.gif.borrow();
+        let (canvas_width, canvas_height) = self.canvas_size();
+
+        let Some(info) = self.frames.first() else {
+            return Ok(self);
+        };
+
+        let &ImageDescriptor {
+            image_left,
+            image_top,
+            image_width,
+            image_height,
+            ..
+        } = &info.table_based_image(gif)?.image_descriptor;
+
+        let width = canvas_width.max(image_left.saturating_add(image_width));
+        let height = canvas_height.max(image_top.saturating_add(image_height));
+
+        if (width, height) != (canvas_width, canvas_height) {
+            // frames covering the logical screen may no longer cover the canvas
+            self.frames = index_frames(gif, width, height)?;
+            self.canvas = Canvas::new(width, height, self.canvas.background);
+        }
+
+        Ok(self)
+    }
+
+    /// Sets the pixel format of the buffers written by
+    /// [`next_frame_into`](FrameDecoder::next_frame_into). Defaults to
+    /// [`PixelFormat::Rgba8`].
+    pub const fn with_pixel_format(mut self, pixel_format: PixelFormat) -> Self {
+        self.pixel_format = pixel_format;
+        self
+    }
+
+    /// Sets the number of bytes between the start of consecutive rows in the
+    /// buffers written by [`next_frame_into`](FrameDecoder::next_frame_into).
+    /// Defaults to the width of the canvas times the bytes per pixel.
+    pub const fn with_stride(mut self, stride: usize) -> Self {
+        self.stride = Some(stride);
+        self
+    }
+
+    /// The data stream this decoder composites frames from.
+    pub fn gif(&self) -> &GifDataStream {
+        self.gif.borrow()
+    }
+
+    /// The width and height of the frames this decoder composites. This is the
+    /// size of the logical screen, unless the canvas was
+    /// [grown](FrameDecoder::with_grown_canvas) to fit the first frame.
+    pub const fn canvas_size(&self) -> (u16, u16) {
+        (self.canvas.width, self.canvas.height)
+    }
+
+    /// Warnings about frames whose image data holds fewer or more indices than
+    /// the frame has pixels, at most one per frame, in the order the frames
+    /// were first composited.
+    ///
+    /// Missing pixels are cleared to the background, and extra indices are
+    /// ignored.
+    /// Deviations found while parsing are in
+    /// [`GifDataStream::diagnostics`](crate::gif_data_stream::GifDataStream::diagnostics).
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// The index of every frame in the data stream.
+    pub fn frames(&self) -> &[FrameInfo] {
+        &self.frames
+    }
+
+    /// Composites the next frame.
+    pub fn next_frame(&mut self) -> Result<Option<Frame>> {
+        if self.cursor >= self.frames.len() {
+            return Ok(None);
+        }
+
+        self.emit(None).map(Some)
+    }
+
+    /// Composites the next frame straight into `out`, in the configured pixel
+    /// format and stride.
+    ///
+    /// The decoder reuses its buffers between frames, so once they have grown
+    /// to fit the largest frame this does not allocate.
+    pub fn next_frame_into(&mut self, out: &mut [u8]) -> Result<Option<FrameMeta>> {
+        if self.cursor >= self.frames.len() {
+            return Ok(None);
+        }
+
+        let (canvas_width, canvas_height) = self.canvas_size();
+
+        let row_len = canvas_width as usize * self.pixel_format.bytes_per_pixel();
+        let stride = self.stride.unwrap_or(row_len);
+
+        if stride < row_len {
+            return Err(error!(
+                "Stride of {} bytes is shorter than a row of {} bytes.",
+                stride, row_len
+            ));
+        }
+
+        let len = match canvas_height as usize {
+            0 => 0,
+            rows => stride * (rows - 1) + row_len,
+        };
+
+        if out.len() < len {
+            return Err(error!(
+                "Output buffer is too small. Want to write {} bytes, but buffer is {} long.",
+                len,
+                out.len()
+            ));
+        }
+
+        let delay_time = self.composite(None)?;
+
+        if canvas_width > 0 {
+            for (row, out) in self
+                .canvas
+                .pixels
+                .chunks_exact(canvas_width as usize)
+                .zip(out.chunks_mut(stride))
+            {
+                self.pixel_format.write(row, out)?;
+            }
+        }
+
+        let meta = FrameMeta {
+            index: self.cursor,
+            delay_time,
+        };
+
+        self.dispose();
+
+        Ok(Some(meta))
+    }
+
+    /// Composites the n-th frame, starting from the nearest keyframe at or
+    /// before it. Subsequent calls to [`next_frame`](FrameDecoder::next_frame)
+    /// continue from the frame after it.
+    pub fn seek(&mut self, n: usize) -> Result<Frame> {
+        if n >= self.frames.len() {
+            return Err(error!(
+                "Frame {} is out of bounds, data stream has {} frames.",
+                n,
+                self.frames.len()
+            ));
+        }
+
+        let keyframe = self.frames[..=n]
+            .iter()
+            .rposition(|frame| frame.keyframe)
+            .ok_or_error("Expected the first frame to be a keyframe.")?;
+
+        // compositing forward from the current position is never more work than
+        // starting over from the keyframe
+        if !(keyframe..=n).contains(&self.cursor) {
+            self.canvas.clear();
+            self.cursor = keyframe;
+        }
+
+        while self.cursor < n {
+            self.composite(None)?;
+            self.dispose();
+        }
+
+        self.emit(None)
+    }
+
+    /// Decompresses the image data of the given range of frames into streams
+    /// of color table indices, without compositing them.
+    ///
+    /// The LZW stream of each frame is independent of the others, so with the
+    /// `rayon` feature enabled the frames are decompressed in parallel. Every
+    /// index stream in the range is held in memory at once, so long animations
+    /// are best decompressed a batch of frames at a time.
+    pub fn decompress_index_streams(&self, frames: Range<usize>) -> Result<Vec<Vec<u8>>> {
+        let gif = self.gif();
+        let len = self.frames.len();
+        let frames = self.frames.get(frames.clone()).ok_or_else(|| {
+            error!(
+                "Frames {:?} are out of bounds, data stream has {} frames.",
+                frames, len
+            )
+        })?;
+
+        let decompress = |info: &FrameInfo| lzw::decompress(info.table_based_image(gif)?);
+
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+
+            frames.par_iter().map(decompress).collect()
+        }
+
+        #[cfg(not(feature = "rayon"))]
+        frames.iter().map(decompress).collect()
+    }
+
+    /// Composites the next frame from its index stream, as returned by
+    /// [`decompress_index_streams`](FrameDecoder::decompress_index_streams).
+    pub fn next_frame_from(&mut self, index_stream: &[u8]) -> Result<Option<Frame>> {
+        if self.cursor >= self.frames.len() {
+            return Ok(None);
+        }
+
+        self.emit(Some(index_stream)).map(Some)
+    }
+
+    /// Decompresses and composites every frame as indices into a single color
+    /// table, rather than as RGB pixels.
+    ///
+    /// Indexed frames can only be composited when every frame uses the same
+    /// color table, so this fails if any frame has a Local Color Table that
+    /// differs from the color table of the other frames. If there is no Global
+    /// Color Table, the canvas starts out filled with index 0. Image data that
+    /// is too short or too long is handled as by the other methods, but isn't
+    /// reported in [`diagnostics`](FrameDecoder::diagnostics).
+    pub fn decompress_indexed(&self) -> Result<IndexedFrames> {
+        let (canvas_width, canvas_height) = self.canvas_size();
+        let background_color_index = self.gif().logical_screen_descriptor.background_color_index;
+
+        let gif = self.gif();
+        let mut color_table: Option<&Vec<u8>> = None;
+
+        for (n, info) in self.frames.iter().enumerate() {
+            let frame_color_table = info
+                .table_based_image(gif)?
+                .local_color_table
+                .as_ref()
+                .or(gif.global_color_table.as_ref())
+                .ok_or_error("Failed to find color table.")?;
+
+            match color_table {
+                Some(color_table) if color_table != frame_color_table => {
+                    return Err(error!(
+                        "Frame {} uses a different color table than the frames before it. Indexed frames require every frame to share one color table.",
+                        n
+                    ));
+                }
+                _ => color_table = Some(frame_color_table),
+            }
+        }
+
+        let color_table = color_table
+            .or(gif.global_color_table.as_ref())
+            .cloned()
+            .unwrap_or_default();
+        let color_table_len = color_table.len() / 3;
+
+        let background_index = match gif.global_color_table {
+            Some(_) if background_color_index as usize >= color_table_len => {
+                return Err(error!("Background color is out of bounds"));
+            }
+            Some(_) => background_color_index,
+            None => 0,
+        };
+
+        let mut canvas = Canvas::new(canvas_width, canvas_height, background_index);
+        let mut frames = Vec::with_capacity(self.frames.len());
+
+        for info in &self.frames {
+            let tbi = info.table_based_image(gif)?;
+            let graphic_control_extension = info.graphic_control_extension(gif)?;
+            let index_stream = lzw::decompress(tbi)?;
+
+            canvas.draw(
+                tbi,
+                graphic_control_extension,
+                info.disposal_method,
+                &index_stream,
+                |index| {
+                    if (index as usize) < color_table_len {
+                        Ok(index)
+                    } else {
+                        Err(error!("Color index {} is out of bounds.", index))
+                    }
+                },
+            )?;
+
+            frames.push(IndexedFrame {
+                delay_time: graphic_control_extension.map(|gce| gce.delay_time),
+                indices: canvas.pixels.clone(),
+            });
+
+            canvas.dispose(info.disposal_method);
+        }
+
+        Ok(IndexedFrames {
+            color_table,
+            frames,
+        })
+    }
+
+    fn emit(&mut self, index_stream: Option<&[u8]>) -> Result<Frame> {
+        let delay_time = self.composite(index_stream)?;
+
+        let frame = Frame {
+            delay_time,
+            pixels: self.canvas.pixels.clone(),
+        };
+
+        self.dispose();
+
+        Ok(frame)
+    }
+
+    // draws the frame at the cursor onto the canvas, decompressing its image
+    // data unless the index stream is given, and returns its delay time
+    fn composite(&mut self, index_stream: Option<&[u8]>) -> Result<Option<u16>> {
+        let info = self.frames[self.cursor];
+        let gif = self.gif.borrow();
+        let tbi = info.table_based_image(gif)?;
+        let graphic_control_extension = info.graphic_control_extension(gif)?;
+
+        let color_table = match &tbi.local_color_table {
+            Some(local_color_table) => {
+                parse_color_table_into(local_color_table, &mut self.local_color_table);
+                &self.local_color_table
+            }
+            None => self
+                .global_color_table
+                .as_ref()
+                .ok_or_error("Failed to find color table.")?,
+        };
+
+        let index_stream = match index_stream {
+            Some(index_stream) => index_stream,
+            None => {
+                self.lzw_decoder
+                    .decompress_into(tbi, &mut self.index_stream)?;
+                &self.index_stream
+            }
+        };
+
+        let indices = index_stream.len();
+        let pixels =
+            tbi.image_descriptor.image_width as usize * tbi.image_descriptor.image_height as usize;
+
+        self.canvas.draw(
+            tbi,
+            graphic_control_extension,
+            info.disposal_method,
+            index_stream,
+            |index| {
+                color_table
+                    .get(index as usize)
+                    .copied()
+                    .ok_or_else(|| error!("Color index {} is out of bounds.", index))
+            },
+        )?;
+
+        if indices != pixels && self.reported.insert(info.offset) {
+            // decompression stops once the image data goes past the frame's
+            // pixels, so only the short count is known
+            let message = if indices < pixels {
+                format!(
+                    "Frame {} has {} pixels, but its image data holds {} indices; the missing pixels are cleared to the background.",
+                    self.cursor, pixels, indices
+                )
+            } else {
+                format!(
+                    "Frame {} has {} pixels, but its image data holds more indices; the extra indices are ignored.",
+                    self.cursor, pixels
+                )
+            };
+
+            self.diagnostics
+                .push(Diagnostic::warning(info.offset, message));
+        }
+
+        Ok(graphic_control_extension.map(|gce| gce.delay_time))
+    }
+
+    // disposes of the frame at the cursor and moves onto the next one
+    fn dispose(&mut self) {
+        self.canvas
+            .dispose(self.frames[self.cursor].disposal_method);
+        self.cursor += 1;
+    }
+}
+
+impl<G: Borrow<GifDataStream>> Iterator for FrameDecoder<G> {
+    type Item = Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame().transpose()
+    }
+}
+
+/// The logical screen that frames are composited onto, holding either RGB
+/// pixels or color table indices.
+#[derive(Debug)]
+struct Canvas<T> {
+    width: u16,
+    height: u16,
+    pixels: Vec<T>,
+    background: T,
+
+    // the canvas as it was before the current frame was drawn, kept for frames
+    // that restore to previous
+    previous: Vec<T>,
+}
+
+impl<T: Copy> Canvas<T> {
+    fn new(width: u16, height: u16, background: T) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![background; width as usize * height as usize],
+            background,
+            previous: vec![],
+        }
+    }
+
+    fn clear(&mut self) {
+        self.pixels.fill(self.background);
+    }
+
+    // draws the index stream of a frame, mapping each index through `color`.
+    // pixels outside of the canvas are clipped, pixels past the end of the
+    // index stream are cleared to the background, and extra indices are ignored
+    fn draw(
+        &mut self,
+        tbi: &TableBasedImage,
+        graphic_control_extension: Option<&GraphicControlExtension>,
+        disposal_method: DisposalMethod,
+        index_stream: &[u8],
+        color: impl Fn(u8) -> Result<T>,
+    ) -> Result<()> {
+        let &ImageDescriptor {
+            image_left,
+            image_top,
+            image_width,
+            image_height,
+            ..
+        } = &tbi.image_descriptor;
+
+        if disposal_method == DisposalMethod::RestoreToPrevious {
+            self.previous.clone_from(&self.pixels);
+        }
+
+        let transparent_index = graphic_control_extension
+            .filter(|gce| gce.transparent_color_flag())
+            .map(|gce| gce.transparent_color_index);
+
+        let mut indices = index_stream.iter();
+
+        for row in image_top as usize..image_top as usize + image_height as usize {
+            for column in image_left as usize..image_left as usize + image_width as usize {
+                let index = indices.next();
+
+                if row >= self.height as usize || column >= self.width as usize {
+                    continue;
+                }
+
+                let pixel = match index {
+                    Some(&index) if Some(index) == transparent_index => continue,
+                    Some(&index) => color(index)?,
+                    None => self.background,
+                };
+
+                self.pixels[row * self.width as usize + column] = pixel;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn dispose(&mut self, disposal_method: DisposalMethod) {
+        match disposal_method {
+            DisposalMethod::NotRequired
+            | DisposalMethod::ToBeDefined
+            | DisposalMethod::DoNotDispose => {}
+            DisposalMethod::RestoreToBackground => self.clear(),
+            DisposalMethod::RestoreToPrevious => {
+                self.pixels.copy_from_slice(&self.previous);
+            }
+        }
+    }
+}
+
+fn index_frames(
+    gif: &GifDataStream,
+    canvas_width: u16,
+    canvas_height: u16,
+) -> Result<Vec<FrameInfo>> {
+    let mut blocks_iter = gif.blocks.iter().enumerate();
+    let mut frames: Vec<FrameInfo> = vec![];
+
+    // whether the canvas is cleared to the background color before the next
+    // frame is drawn
+    let mut clean_canvas = true;
+
+    while let Some((mut index, mut block)) = blocks_iter.next() {
+        if block.special_purpose_block() {
+            continue;
+        }
+
+        let graphic_control_extension = if let Block::GraphicControlExtension(gce) = block {
+            let gce_index = index;
+
+            (index, block) = blocks_iter
+                .next()
+                .ok_or_error("Expected graphic rending block.")?;
+
+            Some((gce_index, gce))
+        } else {
+            None
+        };
+
+        match block {
+            Block::PlainTextExtension(_) => continue,
+            Block::TableBasedImage(tbi) => {
+                let ImageDescriptor {
+                    image_left,
+                    image_top,
+                    image_width,
+                    image_height,
+                    ..
+                } = tbi.image_descriptor;
+
+                let disposal_method = graphic_control_extension
+                    .map_or(DisposalMethod::NotRequired, |(_, gce)| {
+                        gce.disposal_method()
+                    });
+
+                let opaque =
+                    graphic_control_extension.is_none_or(|(_, gce)| !gce.transparent_color_flag());
+
+                let covers_canvas = image_left == 0
+                    && image_top == 0
+                    && image_width >= canvas_width
+                    && image_height >= canvas_height;
+
+                frames.push(FrameInfo {
+                    block: index,
+                    graphic_control_extension: graphic_control_extension.map(|(i, _)| i),
+                    offset: gif.block_offsets[index],
+                    keyframe: clean_canvas
+                        || (opaque
+                            && covers_canvas
+                            && disposal_method != DisposalMethod::RestoreToPrevious),
+                    disposal_method,
+                });
+
+                clean_canvas = match disposal_method {
+                    DisposalMethod::RestoreToBackground => true,
+                    DisposalMethod::RestoreToPrevious => clean_canvas,
+                    _ => false,
+                };
+            }
+            _ => return Err(error!("Encountered an out of order Block.")),
+        }
+    }
+
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use crate::dump_gif;
+    use crate::grammar::label::{IMAGE_DESCRIPTOR, TRAILER};
+    use crate::{Decoder, Severity};
+
+    use super::*;
+
+    const COLOR_TABLE: [u8; 6] = [0, 0, 0, 0xFF, 0xFF, 0xFF];
+
+    // a 2x2 GIF with a black background, and a single white image
+    fn gif_with_image(left: u16, top: u16, width: u16, height: u16) -> Result<GifDataStream> {
+        gif_with_indices(left, top, width, height, width as usize * height as usize)
+    }
+
+    fn gif_with_indices(
+        left: u16,
+        top: u16,
+        width: u16,
+        height: u16,
+        indices: usize,
+    ) -> Result<GifDataStream> {
+        gif(&[image(left, top, width, height, indices, 1)])
+    }
+
+    // a 2x2 GIF with a black background and the given images
+    fn gif(images: &[Vec<u8>]) -> Result<GifDataStream> {
+        let mut data = b"GIF89a".to_vec();
+        data.extend([2, 0, 2, 0, 0b1000_0000, 0, 0]);
+        data.extend(COLOR_TABLE);
+        data.extend(images.concat());
+        data.push(TRAILER);
+
+        Decoder::new(data).parse()
+    }
+
+    // an image whose data holds the given number of indices, all of the same
+    // color. every index is coded after a clear code, so the code size stays
+    // at 3 bits
+    fn image(left: u16, top: u16, width: u16, height: u16, indices: usize, index: u8) -> Vec<u8> {
+        let mut data = vec![IMAGE_DESCRIPTOR];
+        for field in [left, top, width, height] {
+            data.extend(field.to_le_bytes());
+        }
+        data.extend([0, 2]);
+
+        let codes = (0..indices).flat_map(|_| [4, index]).chain([5]);
+        let mut image_data = vec![];
+        for (n, code) in codes.enumerate() {
+            let bit = n * 3;
+            if bit / 8 + 1 >= image_data.len() {
+                image_data.extend([0, 0]);
+            }
+            let shifted = (code as u16) << (bit % 8);
+            image_data[bit / 8] |= shifted as u8;
+            image_data[bit / 8 + 1] |= (shifted >> 8) as u8;
+        }
+
+        for chunk in image_data.chunks(255) {
+            data.push(chunk.len() as u8);
+            data.extend(chunk);
+        }
+        data.push(0);
+
+        data
+    }
+
+    #[test]
+    fn frames_are_clipped_to_the_logical_screen() -> Result<()> {
+        let [black, white] = parse_color_table(&COLOR_TABLE)[..] else {
+            unreachable!()
+        };
+
+        let frames = gif_with_image(1, 1, 3, 3)?.decompress()?;
+        assert_eq!(frames[0].pixels, [black, black, black, white]);
+
+        let frames = gif_with_image(2, 0, 1, 1)?.decompress()?;
+        assert_eq!(frames[0].pixels, [black; 4]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn short_and_long_image_data() -> Result<()> {
+        let [black, white] = parse_color_table(&COLOR_TABLE)[..] else {
+            unreachable!()
+        };
+
+        let gif = gif_with_indices(0, 0, 2, 2, 3)?;
+        let mut frame_decoder = gif.frame_decoder()?;
+
+        let frame = frame_decoder.next_frame()?.unwrap();
+        assert_eq!(frame.pixels, [white, white, white, black]);
+
+        frame_decoder.seek(0)?;
+        let diagnostics = frame_decoder.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].offset, frame_decoder.frames()[0].offset);
+        assert!(diagnostics[0].message.contains("cleared to the background"));
+
+        let gif = gif_with_indices(0, 0, 2, 2, 5)?;
+        let mut frame_decoder = gif.frame_decoder()?;
+
+        let frame = frame_decoder.next_frame()?.unwrap();
+        assert_eq!(frame.pixels, [white; 4]);
+        assert!(frame_decoder.diagnostics()[0].message.contains("ignored"));
+
+        // the missing pixels don't show the white frame before them
+        let short = self::gif(&[image(0, 0, 2, 2, 4, 1), image(0, 0, 2, 2, 3, 0)])?;
+        assert_eq!(short.decompress()?[1].pixels, [black; 4]);
+
+        let mut frame_decoder = short.frame_decoder()?.with_transparent_background();
+        frame_decoder.next_frame()?;
+        let frame = frame_decoder.next_frame()?.unwrap();
+        assert_eq!(frame.pixels, [black, black, black, TRANSPARENT]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn grown_canvas_fits_the_first_frame() -> Result<()> {
+        let [black, white] = parse_color_table(&COLOR_TABLE)[..] else {
+            unreachable!()
+        };
+
+        let gif = gif_with_image(1, 0, 2, 3)?;
+        let mut frame_decoder = gif.frame_decoder()?.with_grown_canvas()?;
+        assert_eq!(frame_decoder.canvas_size(), (3, 3));
+
+        let frame = frame_decoder.next_frame()?.unwrap();
+        assert_eq!(frame.pixels, [black, white, white].repeat(3));
+
+        // frames within the logical screen leave it as it is
+        let gif = gif_with_image(0, 0, 1, 1)?;
+        assert_eq!(
+            gif.frame_decoder()?.with_grown_canvas()?.canvas_size(),
+            (2, 2)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn seek_matches_sequential_decoding() -> Result<()> {
+        let data = dump_gif("../sample_gifs/lady-dance.gif")?;
+        let gif = Decoder::new(data).parse()?;
+
+        let frames = gif.decompress()?;
+        let mut frame_decoder = gif.frame_decoder()?;
+
+        assert!(frame_decoder.frames()[0].keyframe);
+
+        for n in (0..frames.len()).rev() {
+            assert_eq!(frame_decoder.seek(n)?.pixels, frames[n].pixels);
+        }
+
+        assert_eq!(frame_decoder.seek(3)?.pixels, frames[3].pixels);
+        assert_eq!(
+            frame_decoder.next_frame()?.unwrap().pixels,
+            frames[4].pixels
+        );
+
+        // the second frame covers the canvas even though its image data is
+        // too short, as the missing pixels are cleared
+        let short = self::gif(&[image(0, 0, 2, 2, 4, 1), image(0, 0, 2, 2, 3, 0)])?;
+        let frames = short.decompress()?;
+        let mut frame_decoder = short.frame_decoder()?;
+
+        assert!(frame_decoder.frames()[1].keyframe);
+        assert_eq!(frame_decoder.seek(1)?.pixels, frames[1].pixels);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn index_streams_match_frames() -> Result<()> {
+        let data = dump_gif("../sample_gifs/sample_2_animation.gif")?;
+        let gif = Decoder::new(data).parse()?;
+
+        let frames = gif.decompress()?;
+        let mut frame_decoder = gif.frame_decoder()?;
+
+        let index_streams = frame_decoder.decompress_index_streams(1..3)?;
+        assert_eq!(index_streams.len(), 2);
+
+        frame_decoder.seek(0)?;
+        for (index_stream, frame) in index_streams.iter().zip(&frames[1..]) {
+            let composited = frame_decoder.next_frame_from(index_stream)?.unwrap();
+            assert_eq!(composited.pixels, frame.pixels);
+        }
+
+        assert!(frame_decoder.decompress_index_streams(2..4).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn indexed_frames_match_rgb_frames() -> Result<()> {
+        let data = dump_gif("../sample_gifs/sample_2_animation.gif")?;
+        let gif = Decoder::new(data).parse()?;
+
+        let frames = gif.decompress()?;
+        let indexed = gif.decompress_indexed()?;
+        let color_table = parse_color_table(&indexed.color_table);
+
+        assert_eq!(indexed.frames.len(), frames.len());
+
+        for (frame, indexed_frame) in frames.iter().zip(&indexed.frames) {
+            let pixels: Vec<u32> = indexed_frame
+                .indices
+                .iter()
+                .map(|index| color_table[*index as usize])
+                .collect();
+
+            assert_eq!(pixels, frame.pixels);
+            assert_eq!(indexed_frame.delay_time, frame.delay_time);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn next_frame_into_matches_frames() -> Result<()> {
+        let data = dump_gif("../sample_gifs/sample_2_animation.gif")?;
+        let gif = Decoder::new(data).parse()?;
+
+        let frames = gif.decompress()?;
+        let width = gif.logical_screen_descriptor.canvas_width as usize;
+        let stride = width * 3 + 5;
+
+        let mut frame_decoder = gif
+            .frame_decoder()?
+            .with_pixel_format(PixelFormat::Rgb8)
+            .with_stride(stride);
+
+        let mut out = vec![0; stride * gif.logical_screen_descriptor.canvas_height as usize];
+        let mut expected = vec![0; width * 3];
+
+        for (n, frame) in frames.iter().enumerate() {
+            let meta = frame_decoder.next_frame_into(&mut out)?.unwrap();
+            assert_eq!(meta.index, n);
+            assert_eq!(meta.delay_time, frame.delay_time);
+
+            for (row, pixels) in out.chunks(stride).zip(frame.pixels.chunks(width)) {
+                PixelFormat::Rgb8.write(pixels, &mut expected)?;
+                assert_eq!(&row[..width * 3], expected.as_slice());
+            }
+        }
+
+        assert!(frame_decoder.next_frame_into(&mut out)?.is_none());
+
+        Ok(())
+    }
+}
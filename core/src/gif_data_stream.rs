@@ -1,13 +1,15 @@
-use std::fmt::Debug;
+use alloc::vec::Vec;
+use core::fmt::Debug;
 
-use eyre::{eyre, Ok, OptionExt, Result};
-
-use crate::bitstream::BitStream;
+use crate::diagnostic::Diagnostic;
+use crate::error::Result;
+use crate::frame_decoder::FrameDecoder;
 use crate::grammar::{
-    ApplicationExtension, build_code_table, CommentExtension, DEFAULT_BACKGROUND_COLOR, DisposalMethod,
-    Frame, GraphicControlExtension, ImageDescriptor, LogicalScreenDescriptor, parse_color_table,
-    PlainTextExtension, TableBasedImage,
+    ApplicationExtension, CommentExtension, Frame, GraphicControlExtension, IndexedFrames,
+    LogicalScreenDescriptor, PlainTextExtension, TableBasedImage,
 };
+use crate::sheet::{Sheet, SheetOptions};
+use crate::version::Version;
 
 #[derive(Debug)]
 pub enum Block {
@@ -19,7 +21,7 @@ pub enum Block {
 }
 
 impl Block {
-    const fn special_purpose_block(&self) -> bool {
+    pub(crate) const fn special_purpose_block(&self) -> bool {
         matches!(
             self,
             Self::ApplicationExtension(_) | Self::CommentExtension(_)
@@ -29,212 +31,91 @@ impl Block {
 
 #[derive(Debug)]
 pub struct GifDataStream {
-    pub version: String,
+    pub version: Version,
     pub logical_screen_descriptor: LogicalScreenDescriptor,
     pub global_color_table: Option<Vec<u8>>,
     pub blocks: Vec<Block>,
+
+    /// Byte offset of every block in `blocks`, measured from the start of the
+    /// data stream to the block's introducer.
+    pub block_offsets: Vec<usize>,
+
+    /// Deviations from the specification found while parsing.
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 impl GifDataStream {
+    /// Decompresses and composites every frame in the data stream.
+    ///
+    /// With the `rayon` feature enabled, the image data of a batch of frames
+    /// is decompressed in parallel before those frames are composited in
+    /// order, so only the index streams of one batch are held in memory.
     pub fn decompress(&self) -> Result<Vec<Frame>> {
+        #[cfg(feature = "rayon")]
+        {
+            let mut frame_decoder = FrameDecoder::new(self)?;
+            let len = frame_decoder.frames().len();
+            let batch = rayon::current_num_threads() * 2;
+            let mut frames = Vec::with_capacity(len);
+
+            for start in (0..len).step_by(batch) {
+                let end = len.min(start + batch);
+
+                for index_stream in frame_decoder.decompress_index_streams(start..end)? {
+                    frames.extend(frame_decoder.next_frame_from(&index_stream)?);
+                }
+            }
+
+            Ok(frames)
+        }
+
+        #[cfg(not(feature = "rayon"))]
+        FrameDecoder::new(self)?.collect()
+    }
+
+    /// Decompresses and composites every frame as indices into a single color
+    /// table. See [`FrameDecoder::decompress_indexed`].
+    pub fn decompress_indexed(&self) -> Result<IndexedFrames> {
+        FrameDecoder::new(self)?.decompress_indexed()
+    }
+
+    /// Decompresses every frame and tiles them into a single image. See
+    /// [`Sheet::new`].
+    pub fn contact_sheet(&self, options: &SheetOptions) -> Result<Sheet> {
         let LogicalScreenDescriptor {
             canvas_width,
             canvas_height,
-            background_color_index,
             ..
         } = self.logical_screen_descriptor;
 
-        let global_color_table = self
-            .global_color_table
-            .as_ref()
-            .map(|t| parse_color_table(t.as_slice()));
-
-        let background_color = match global_color_table.as_ref() {
-            Some(gct) => *gct
-                .get(background_color_index as usize)
-                .ok_or_eyre("Background color is out of bounds")?,
-            None => DEFAULT_BACKGROUND_COLOR,
-        };
-
-        let mut canvas = { vec![background_color; canvas_width as usize * canvas_height as usize] };
-
-        let mut blocks_iter = self.blocks.iter();
-        let mut frames = vec![];
+        Sheet::new(
+            &self.decompress()?,
+            canvas_width as usize,
+            canvas_height as usize,
+            options,
+        )
+    }
 
-        while let Some(mut block) = blocks_iter.next() {
-            if block.special_purpose_block() {
-                continue;
-            }
+    /// Creates a [`FrameDecoder`] that composites the frames of this data
+    /// stream one at a time.
+    pub fn frame_decoder(&self) -> Result<FrameDecoder<&Self>> {
+        FrameDecoder::new(self)
+    }
 
-            let graphic_control_extension = if let Block::GraphicControlExtension(gce) = block {
-                block = blocks_iter
-                    .next()
-                    .ok_or_eyre("Expected graphic rending block.")?;
-
-                Some(gce)
-            } else {
-                None
-            };
-
-            match block {
-                Block::PlainTextExtension(_) => continue,
-                Block::TableBasedImage(tbi) => {
-                    let TableBasedImage {
-                        image_descriptor,
-                        image_data,
-                        local_color_table,
-                        lzw_minimum_code,
-                    } = tbi;
-
-                    let local_color_table = local_color_table
-                        .as_ref()
-                        .map(|t| parse_color_table(t.as_slice()));
-                    let color_table = local_color_table
-                        .as_ref()
-                        .or(global_color_table.as_ref())
-                        .ok_or_eyre("Failed to find color table.")?;
-
-                    let mut code_table = build_code_table(color_table.len());
-
-                    let clear_code_key = 2_usize
-                        .checked_pow(*lzw_minimum_code as u32)
-                        .ok_or_eyre("Integer overflow when computing clear code key.")?;
-                    let eoi_code = clear_code_key + 1;
-
-                    let mut bitstream = BitStream::new(image_data);
-                    let mut current_code_len = *lzw_minimum_code as usize + 1;
-                    let mut index_stream = vec![];
-                    let mut prev_code = usize::MAX;
-
-                    while !bitstream.eof(current_code_len) {
-                        let next_code = bitstream.next(current_code_len)?;
-
-                        if next_code == clear_code_key {
-                            current_code_len = (*lzw_minimum_code + 1) as usize;
-                            code_table = build_code_table(color_table.len());
-
-                            let code = bitstream.next(current_code_len)?;
-                            index_stream.extend(
-                                code_table
-                                    .get(code)
-                                    .ok_or_else(|| eyre!("Code {} not found in code table.", code))?
-                                    .clone(),
-                            );
-                            prev_code = code;
-                            continue;
-                        }
-
-                        if next_code == eoi_code {
-                            break;
-                        }
-
-                        if prev_code == usize::MAX {
-                            return Err(eyre!("Expected initial code to be the clear code key. Got prev_code as usize::Max."));
-                        }
-
-                        if next_code < code_table.len() {
-                            let colors = &code_table[next_code];
-                            index_stream.extend(colors.clone());
-
-                            let k = colors.first().ok_or_eyre("Failed to get any color")?;
-
-                            let mut new_colors = code_table
-                                .get(prev_code)
-                                .ok_or_eyre("Code not found in code table.")?
-                                .clone();
-                            new_colors.push(*k);
-                            code_table.push(new_colors);
-                        } else {
-                            let colors = code_table
-                                .get(prev_code)
-                                .ok_or_eyre("Code not found in code table.")?;
-
-                            let k = colors.first().ok_or_eyre("Failed to get color")?;
-
-                            let mut new_sequence = colors.clone();
-                            new_sequence.push(*k);
-
-                            index_stream.extend(new_sequence.clone());
-                            code_table.push(new_sequence.clone());
-                        }
-
-                        prev_code = next_code;
-
-                        if code_table.len() == 2usize.pow(current_code_len as u32) {
-                            current_code_len += 1;
-                        }
-                    }
-
-                    let frame: Vec<u32> = index_stream
-                        .iter()
-                        .map(|index| color_table[*index])
-                        .collect();
-
-                    let &ImageDescriptor {
-                        image_left,
-                        image_top,
-                        image_width,
-                        image_height,
-                        ..
-                    } = image_descriptor;
-
-                    let transparent_color = graphic_control_extension.as_ref().and_then(|gce| {
-                        if gce.transparent_color_flag() {
-                            Some(color_table[gce.transparent_color_index as usize])
-                        } else {
-                            None
-                        }
-                    });
-
-                    let mut frame_coord = 0;
-
-                    for row in image_top..image_top + image_height {
-                        for i in 0..image_width {
-                            let canvas_coord = (row as usize * canvas_width as usize)
-                                + image_left as usize
-                                + i as usize;
-
-                            if frame_coord >= frame.len() {
-                                return Err(eyre!("Improper slice into a Frame. Want to index {}, but frame is {} long.", frame_coord, frame.len()));
-                            }
-
-                            if transparent_color.is_none()
-                                || Some(frame[frame_coord]) != transparent_color
-                            {
-                                canvas[canvas_coord] = frame[frame_coord];
-                            }
-
-                            frame_coord += 1;
-                        }
-                    }
-
-                    frames.push(Frame {
-                        delay_time: graphic_control_extension.map(|gce| gce.delay_time),
-                        pixels: canvas.clone(),
-                    });
-
-                    if let Some(gce) = graphic_control_extension {
-                        match gce.disposal_method() {
-                            DisposalMethod::NotRequired
-                            | DisposalMethod::ToBeDefined
-                            | DisposalMethod::DoNotDispose => {}
-                            DisposalMethod::RestoreToBackground => {
-                                canvas.fill(background_color);
-                            }
-                            DisposalMethod::RestoreToPrevious => {
-                                let Frame { pixels, .. } = frames.last().ok_or_eyre(
-                                    "Expected previous frame to exist, frames empty.",
-                                )?;
-
-                                canvas.copy_from_slice(pixels);
-                            }
-                        }
-                    }
-                }
-                _ => return Err(eyre!("Encountered an out of order Block.")),
+    /// The number of times the animation repeats after being displayed once,
+    /// as set by the NETSCAPE2.0 application extension. 0 repeats forever,
+    /// and `None` means the animation is displayed once.
+    pub fn loop_count(&self) -> Option<u16> {
+        self.blocks.iter().find_map(|block| match block {
+            Block::ApplicationExtension(extension)
+                if extension.identifier == "NETSCAPE"
+                    && &extension.authentication_code == b"2.0"
+                    && extension.data.len() == 3
+                    && extension.data[0] == 1 =>
+            {
+                Some(u16::from_le_bytes([extension.data[1], extension.data[2]]))
             }
-        }
-
-        Ok(frames)
+            _ => None,
+        })
     }
 }
@@ -1,4 +1,11 @@
-use std::fmt::Debug;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use core::time::Duration;
+
+use crate::delay::DelayPolicy;
+use crate::error::Result;
+use crate::pixel_format::PixelFormat;
 
 pub mod label {
     pub const EXTENSION: u8 = 0x21;
@@ -19,18 +26,19 @@ pub struct ApplicationExtension {
 
 pub type RGB = (u8, u8, u8);
 
-pub fn build_code_table(size: usize) -> Vec<Vec<usize>> {
-    (0..size + 2).map(|c| vec![c]).collect()
+pub fn parse_color_table(color_table: &[u8]) -> Vec<u32> {
+    let mut colors = Vec::with_capacity(color_table.len() / 3);
+    parse_color_table_into(color_table, &mut colors);
+    colors
 }
 
-pub fn parse_color_table(color_table: &[u8]) -> Vec<u32> {
-    color_table
-        .chunks_exact(3)
-        .map(|chunk| {
-            let (r, g, b) = (chunk[0], chunk[1], chunk[2]);
-            u32::from_be_bytes([0u8, r, g, b])
-        })
-        .collect::<Vec<_>>()
+/// Parses a color table into `colors`, replacing its contents.
+pub fn parse_color_table_into(color_table: &[u8], colors: &mut Vec<u32>) {
+    colors.clear();
+    colors.extend(color_table.chunks_exact(3).map(|chunk| {
+        let (r, g, b) = (chunk[0], chunk[1], chunk[2]);
+        u32::from_be_bytes([0u8, r, g, b])
+    }));
 }
 
 #[derive(Debug)]
@@ -38,7 +46,7 @@ pub struct CommentExtension {
     pub data: Vec<u8>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DisposalMethod {
     NotRequired,
     DoNotDispose,
@@ -57,6 +65,23 @@ impl DisposalMethod {
             _ => Self::ToBeDefined,
         }
     }
+
+    /// The disposal method in snake case, e.g. `restore_to_background`.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::NotRequired => "not_required",
+            Self::DoNotDispose => "do_not_dispose",
+            Self::RestoreToBackground => "restore_to_background",
+            Self::RestoreToPrevious => "restore_to_previous",
+            Self::ToBeDefined => "to_be_defined",
+        }
+    }
+}
+
+impl fmt::Display for DisposalMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 /// The GraphicControlExtension contains parameters used when processing a
@@ -165,6 +190,11 @@ impl ImageDescriptor {
 
 pub const DEFAULT_BACKGROUND_COLOR: u32 = 0_u32;
 
+/// Marks canvas pixels that no frame has painted when compositing onto a
+/// transparent background. Colors never set the top byte, so this can not
+/// collide with a real `0x00RRGGBB` pixel.
+pub const TRANSPARENT: u32 = 0xFF00_0000;
+
 /// The LogicalScreenDescriptor contains the parameters necessary to define the
 /// area of the display device within which the images will be rendered.
 ///
@@ -297,3 +327,51 @@ pub struct Frame {
     pub delay_time: Option<u16>,
     pub pixels: Vec<u32>,
 }
+
+/// A composited frame whose pixels are indices into the color table of the
+/// [`IndexedFrames`] it belongs to.
+#[derive(Debug)]
+pub struct IndexedFrame {
+    pub delay_time: Option<u16>,
+    pub indices: Vec<u8>,
+}
+
+/// Frames composited as color table indices, along with the color table they
+/// index into.
+#[derive(Debug)]
+pub struct IndexedFrames {
+    /// The color table shared by every frame, as RGB triplets.
+    pub color_table: Vec<u8>,
+    pub frames: Vec<IndexedFrame>,
+}
+
+/// Describes a frame written into a caller-provided buffer by
+/// [`FrameDecoder::next_frame_into`](crate::FrameDecoder::next_frame_into).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameMeta {
+    /// Position of the frame in the data stream.
+    pub index: usize,
+    pub delay_time: Option<u16>,
+}
+
+impl FrameMeta {
+    /// How long this frame should be displayed for under the given
+    /// [`DelayPolicy`].
+    pub fn delay(&self, policy: DelayPolicy) -> Duration {
+        policy.duration(self.delay_time)
+    }
+}
+
+impl Frame {
+    /// How long this frame should be displayed for under the given
+    /// [`DelayPolicy`].
+    pub fn delay(&self, policy: DelayPolicy) -> Duration {
+        policy.duration(self.delay_time)
+    }
+
+    /// Writes the pixels of this frame into `out` in the given
+    /// [`PixelFormat`].
+    pub fn write_pixels(&self, format: PixelFormat, out: &mut [u8]) -> Result<()> {
+        format.write(&self.pixels, out)
+    }
+}
@@ -0,0 +1,183 @@
+//! Implementations of the [`image`](::image) crate's decoder traits, so `jif`
+//! can stand in for its built-in GIF backend.
+
+use std::ffi::OsString;
+use std::io::Read;
+use std::num::NonZeroU32;
+
+use ::image::error::{DecodingError, ImageFormatHint};
+use ::image::hooks::{register_decoding_hook, register_format_detection_hook};
+use ::image::metadata::LoopCount;
+use ::image::{
+    AnimationDecoder, ColorType, Delay, Frames, ImageDecoder, ImageError, ImageFormat, ImageResult,
+    RgbaImage,
+};
+
+use crate::gif_data_stream::GifDataStream;
+use crate::{Decoder, DelayPolicy, FrameDecoder, PixelFormat};
+
+/// Decodes GIFs through the [`ImageDecoder`] and [`AnimationDecoder`] traits.
+///
+/// Like the built-in decoder of the `image` crate, frames are composited onto
+/// a transparent canvas and written as RGBA. Every frame covers the entire
+/// canvas.
+pub struct GifDecoder {
+    frame_decoder: FrameDecoder<GifDataStream>,
+    delay_policy: DelayPolicy,
+}
+
+impl GifDecoder {
+    /// Reads and parses an entire GIF data stream.
+    pub fn new(reader: impl Read) -> ImageResult<Self> {
+        let gif = Decoder::from_reader(reader)
+            .and_then(|mut decoder| decoder.parse())
+            .map_err(decoding_error)?;
+
+        Self::from_gif(gif)
+    }
+
+    pub fn from_gif(gif: GifDataStream) -> ImageResult<Self> {
+        let frame_decoder = FrameDecoder::new(gif)
+            .map_err(decoding_error)?
+            .with_transparent_background()
+            .with_pixel_format(PixelFormat::Rgba8);
+
+        Ok(Self {
+            frame_decoder,
+            delay_policy: DelayPolicy::default(),
+        })
+    }
+
+    /// Sets how the delay of each frame is computed. Defaults to
+    /// [`DelayPolicy::BrowserCompatible`]; use [`DelayPolicy::Raw`] to match
+    /// the built-in decoder of the `image` crate.
+    pub const fn with_delay_policy(mut self, delay_policy: DelayPolicy) -> Self {
+        self.delay_policy = delay_policy;
+        self
+    }
+
+    fn buffer_len(&self) -> usize {
+        let (width, height) = self.dimensions();
+        width as usize * height as usize * PixelFormat::Rgba8.bytes_per_pixel()
+    }
+}
+
+impl ImageDecoder for GifDecoder {
+    fn dimensions(&self) -> (u32, u32) {
+        let (width, height) = self.frame_decoder.canvas_size();
+        (width as u32, height as u32)
+    }
+
+    fn color_type(&self) -> ColorType {
+        ColorType::Rgba8
+    }
+
+    /// Writes the first frame into `buf`.
+    fn read_image(mut self, buf: &mut [u8]) -> ImageResult<()> {
+        self.frame_decoder
+            .next_frame_into(buf)
+            .map_err(decoding_error)?
+            .ok_or_else(|| decoding_error(crate::Error::new("GIF has no frames.")))?;
+
+        Ok(())
+    }
+
+    fn read_image_boxed(self: Box<Self>, buf: &mut [u8]) -> ImageResult<()> {
+        (*self).read_image(buf)
+    }
+}
+
+impl<'a> AnimationDecoder<'a> for GifDecoder {
+    fn into_frames(mut self) -> Frames<'a> {
+        let (width, height) = self.dimensions();
+        let len = self.buffer_len();
+
+        let frames = std::iter::from_fn(move || {
+            let mut buffer = vec![0; len];
+
+            let frame = match self.frame_decoder.next_frame_into(&mut buffer) {
+                Ok(Some(meta)) => meta,
+                Ok(None) => return None,
+                Err(err) => return Some(Err(decoding_error(err))),
+            };
+
+            let image = RgbaImage::from_raw(width, height, buffer)
+                .expect("The buffer holds an entire frame.");
+            let delay = Delay::from_saturating_duration(frame.delay(self.delay_policy));
+
+            Some(Ok(::image::Frame::from_parts(image, 0, 0, delay)))
+        });
+
+        Frames::new(Box::new(frames))
+    }
+
+    fn loop_count(&self) -> LoopCount {
+        match self.frame_decoder.gif().loop_count() {
+            None => LoopCount::Finite(NonZeroU32::MIN),
+            Some(0) => LoopCount::Infinite,
+            // the animation is displayed once before it starts repeating
+            Some(loop_count) => {
+                LoopCount::Finite(NonZeroU32::MIN.saturating_add(loop_count as u32))
+            }
+        }
+    }
+}
+
+/// Registers [`GifDecoder`] with the `image` crate, so readers that guess
+/// their format, such as `ImageReader::with_guessed_format` and
+/// `image::load_from_memory`, decode GIFs with `jif`.
+///
+/// Returns false if another decoding hook is already registered for GIFs.
+pub fn register() -> bool {
+    register_format_detection_hook(OsString::from("gif"), b"GIF87a", None);
+    register_format_detection_hook(OsString::from("gif"), b"GIF89a", None);
+
+    register_decoding_hook(
+        OsString::from("gif"),
+        Box::new(|reader| Ok(Box::new(GifDecoder::new(reader)?))),
+    )
+}
+
+fn decoding_error(err: crate::Error) -> ImageError {
+    ImageError::Decoding(DecodingError::new(
+        ImageFormatHint::Exact(ImageFormat::Gif),
+        err,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use ::image::{DynamicImage, ImageReader};
+
+    use super::*;
+    use crate::dump_gif;
+
+    #[test]
+    fn decode() -> ImageResult<()> {
+        let data = dump_gif("../sample_gifs/sample_2_animation.gif").unwrap();
+
+        let decoder = GifDecoder::new(Cursor::new(&data))?;
+        let (width, height) = decoder.dimensions();
+        let frames = decoder.into_frames().collect_frames()?;
+
+        let gif = Decoder::new(data.clone()).parse().unwrap();
+        let expected = gif.decompress().unwrap();
+        assert_eq!(frames.len(), expected.len());
+
+        for frame in &frames {
+            assert_eq!(frame.buffer().dimensions(), (width, height));
+        }
+
+        // the crate is built without its own GIF backend, so this only
+        // succeeds through the registered hook
+        register();
+        let image = ImageReader::new(Cursor::new(&data))
+            .with_guessed_format()?
+            .decode()?;
+        assert_eq!(image, DynamicImage::ImageRgba8(frames[0].buffer().clone()));
+
+        Ok(())
+    }
+}
@@ -1,19 +1,57 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(clippy::nursery)]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
 use std::fs::File;
+#[cfg(feature = "std")]
 use std::io::Read;
+#[cfg(feature = "std")]
 use std::path::PathBuf;
 
-use eyre::Result;
-
 pub use decode::Decoder;
+pub use delay::DelayPolicy;
+pub use diagnostic::{Diagnostic, Severity};
+pub use error::{Error, Result};
+pub use frame_decoder::{FrameDecoder, FrameInfo};
+pub use pixel_format::PixelFormat;
+pub use sheet::{Sheet, SheetFrame, SheetOptions};
+pub use validate::validate;
+pub use version::{Version, VersionPolicy};
 
 mod bitstream;
 mod buffer;
 mod decode;
+mod delay;
+mod diagnostic;
+mod error;
+mod frame_decoder;
 pub mod gif_data_stream;
 pub mod grammar;
+#[cfg(feature = "image")]
+pub mod image;
+mod lzw;
+mod pixel_format;
+mod sheet;
+pub mod validate;
+mod version;
+
+/// Parsers for the command line flags of the binaries in this workspace. Not
+/// part of the public API.
+#[doc(hidden)]
+pub mod flags {
+    pub use crate::pixel_format::parse_color;
+}
+
+/// Internals used by the benchmarks in `benches/`. Not part of the public API.
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench {
+    pub use crate::bitstream::BitStream;
+}
 
+#[cfg(feature = "std")]
 pub fn dump_gif(path: &str) -> Result<Vec<u8>> {
     let path = PathBuf::from(path);
     let mut file = File::open(&path)?;
@@ -0,0 +1,357 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::bitstream::BitStream;
+use crate::error::{error, Result};
+use crate::grammar::TableBasedImage;
+
+/// Decompresses the LZW encoded image data of a TableBasedImage into a stream
+/// of color table indices. Color tables hold at most 256 colors, so each index
+/// fits in a byte.
+pub fn decompress(image: &TableBasedImage) -> Result<Vec<u8>> {
+    let mut index_stream = vec![];
+    LzwDecoder::default().decompress_into(image, &mut index_stream)?;
+    Ok(index_stream)
+}
+
+/// The most codes a code table can hold, as codes are at most 12 bits.
+const MAX_CODES: usize = 1 << 12;
+
+/// A deviation from the specification found while decompressing, reported
+/// along with the position, in bits from the start of the image data, of the
+/// code it was found at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LzwEvent {
+    /// The image data does not start with a clear code.
+    MissingClearCode,
+
+    /// A code is neither in the code table nor the next one to be added to
+    /// it. Decompression stops here.
+    UnknownCode { code: usize, table_len: usize },
+
+    /// A code stands for a color index beyond the color table. Only the first
+    /// such code is reported.
+    IndexOutOfRange { index: usize },
+
+    /// The image data holds more indices than the image has pixels.
+    /// Decompression stops after the code that goes past the limit.
+    ExtraIndices,
+
+    /// Bits are set after the end of information code.
+    DataAfterEnd,
+
+    /// The image data ends without an end of information code.
+    MissingEnd,
+}
+
+/// A code in the code table, stored as the code of the sequence it extends
+/// and the index appended to it.
+#[derive(Debug, Clone, Copy)]
+struct Code {
+    prefix: Option<usize>,
+    index: u8,
+    first: u8,
+    len: usize,
+
+    /// The largest index in the sequence, which may not fit in a byte when
+    /// the minimum code size is larger than 8.
+    max: usize,
+}
+
+impl Code {
+    const fn root(index: usize) -> Self {
+        Self {
+            prefix: None,
+            index: index as u8,
+            first: index as u8,
+            len: 1,
+            max: index,
+        }
+    }
+
+    // the clear and end of information codes stand for no indices
+    const CONTROL: Self = Self {
+        prefix: None,
+        index: 0,
+        first: 0,
+        len: 0,
+        max: 0,
+    };
+}
+
+/// An LZW decoder that keeps its code table and bit stream between images, so
+/// decompressing frames does not allocate once the buffers have grown to fit.
+#[derive(Debug, Default)]
+pub struct LzwDecoder {
+    code_table: Vec<Code>,
+    bitstream: BitStream,
+}
+
+impl LzwDecoder {
+    /// Decompresses the image data of a TableBasedImage, replacing the
+    /// contents of `index_stream`.
+    ///
+    /// Image data with more indices than the image has pixels is only
+    /// decompressed up to the code that goes past the limit, so such an index
+    /// stream is longer than the image, but not by more than a code.
+    pub fn decompress_into(
+        &mut self,
+        image: &TableBasedImage,
+        index_stream: &mut Vec<u8>,
+    ) -> Result<()> {
+        let descriptor = &image.image_descriptor;
+
+        self.decompress_with(
+            image.image_data.iter().map(Vec::as_slice),
+            image.lzw_minimum_code,
+            usize::from(u8::MAX) + 1,
+            descriptor.image_width as usize * descriptor.image_height as usize,
+            index_stream,
+            |_, _| {},
+        )
+    }
+
+    /// Decompresses image data split into `sub_blocks`, replacing the contents
+    /// of `index_stream`, and passes every [`LzwEvent`] to `report`.
+    /// Decompression stops once `index_stream` is longer than `max_len`.
+    pub fn decompress_with<'a>(
+        &mut self,
+        sub_blocks: impl IntoIterator<Item = &'a [u8]>,
+        lzw_minimum_code: u8,
+        color_table_len: usize,
+        max_len: usize,
+        index_stream: &mut Vec<u8>,
+        mut report: impl FnMut(usize, LzwEvent),
+    ) -> Result<()> {
+        index_stream.clear();
+        self.bitstream.load(sub_blocks);
+
+        // an image without data has no pixels, whatever its code size
+        if self.bitstream.data.is_empty() {
+            report(0, LzwEvent::MissingEnd);
+            return Ok(());
+        }
+
+        if lzw_minimum_code > 11 {
+            return Err(error!(
+                "Invalid LZW minimum code size {}.",
+                lzw_minimum_code
+            ));
+        }
+
+        let clear_code = 1 << lzw_minimum_code;
+        let eoi_code = clear_code + 1;
+
+        self.reset_code_table(clear_code);
+        let mut code_len = lzw_minimum_code as usize + 1;
+        let mut prev_code = None;
+        let mut reported_range = false;
+
+        loop {
+            if self.bitstream.eof(code_len) {
+                report(self.bitstream.data.len() * 8, LzwEvent::MissingEnd);
+                break;
+            }
+
+            let position = self.bitstream.cursor;
+            let code = self.bitstream.next(code_len)?;
+
+            if position == 0 && code != clear_code {
+                report(position, LzwEvent::MissingClearCode);
+            }
+
+            if code == clear_code {
+                self.reset_code_table(clear_code);
+                code_len = lzw_minimum_code as usize + 1;
+                prev_code = None;
+                continue;
+            }
+
+            if code == eoi_code {
+                if self.bitstream.has_set_bits() {
+                    report(self.bitstream.cursor, LzwEvent::DataAfterEnd);
+                }
+
+                break;
+            }
+
+            let table_len = self.code_table.len();
+
+            let entry = match (self.code_table.get(code).copied(), prev_code) {
+                (Some(entry), _) => entry,
+
+                // a code that is not in the table yet stands for the previous
+                // sequence followed by its own first index
+                (None, Some(prev_code)) if code == table_len => {
+                    let prev = self.code_table[prev_code];
+
+                    Code {
+                        prefix: Some(prev_code),
+                        index: prev.first,
+                        first: prev.first,
+                        len: prev.len + 1,
+                        max: prev.max,
+                    }
+                }
+                _ => {
+                    report(position, LzwEvent::UnknownCode { code, table_len });
+                    return Err(error!("Code {} not found in code table.", code));
+                }
+            };
+
+            if entry.max >= color_table_len && !reported_range {
+                report(position, LzwEvent::IndexOutOfRange { index: entry.max });
+                reported_range = true;
+            }
+
+            if entry.max > u8::MAX as usize {
+                return Err(error!("Color index {} is out of bounds.", entry.max));
+            }
+
+            if let Some(prev_code) = prev_code {
+                if table_len < MAX_CODES {
+                    let prev = self.code_table[prev_code];
+
+                    self.code_table.push(Code {
+                        prefix: Some(prev_code),
+                        index: entry.first,
+                        first: prev.first,
+                        len: prev.len + 1,
+                        max: prev.max.max(entry.first as usize),
+                    });
+
+                    if self.code_table.len() == 1 << code_len && code_len < 12 {
+                        code_len += 1;
+                    }
+                }
+            }
+
+            self.write(code, index_stream)
+                .ok_or_else(|| error!("Code {} not found in code table.", code))?;
+
+            prev_code = Some(code);
+
+            // the rest of the image data could not be drawn anyway
+            if index_stream.len() > max_len {
+                report(position, LzwEvent::ExtraIndices);
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn reset_code_table(&mut self, clear_code: usize) {
+        self.code_table.clear();
+        self.code_table.extend((0..clear_code).map(Code::root));
+        self.code_table.extend([Code::CONTROL; 2]);
+    }
+
+    // appends the sequence of indices a code stands for to the index stream
+    fn write(&self, code: usize, index_stream: &mut Vec<u8>) -> Option<()> {
+        let len = self.code_table.get(code)?.len;
+
+        if len == 0 {
+            return None;
+        }
+
+        let start = index_stream.len();
+        index_stream.resize(start + len, 0);
+
+        let mut code = Some(code);
+
+        for index in index_stream[start..].iter_mut().rev() {
+            let entry = self.code_table[code?];
+            *index = entry.index;
+            code = entry.prefix;
+        }
+
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a clear code, 1, 1, the first code added to the table and an end of
+    // information code, with a minimum code size of 2
+    const DATA: [u8; 2] = [0x4C, 0x5C];
+
+    #[test]
+    fn code_table_starts_after_the_control_codes() -> Result<()> {
+        let mut events = vec![];
+        let mut index_stream = vec![];
+
+        // the color table is smaller than the 4 roots the code size implies,
+        // which must not move the codes added to the table
+        LzwDecoder::default().decompress_with(
+            [DATA.as_slice()],
+            2,
+            2,
+            4,
+            &mut index_stream,
+            |bit, event| events.push((bit, event)),
+        )?;
+
+        assert_eq!(index_stream, [1, 1, 1, 1]);
+        assert_eq!(events, []);
+
+        Ok(())
+    }
+
+    #[test]
+    fn events() {
+        let mut events = vec![];
+        let mut index_stream = vec![];
+
+        // a 3, then a code beyond the table of 6 codes
+        let data = [0b0011_1011];
+        let decompressed = LzwDecoder::default().decompress_with(
+            [data.as_slice()],
+            2,
+            2,
+            4,
+            &mut index_stream,
+            |bit, event| events.push((bit, event)),
+        );
+
+        assert!(decompressed.is_err());
+        assert_eq!(
+            events,
+            [
+                (0, LzwEvent::MissingClearCode),
+                (0, LzwEvent::IndexOutOfRange { index: 3 }),
+                (
+                    3,
+                    LzwEvent::UnknownCode {
+                        code: 7,
+                        table_len: 6
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_past_the_limit() -> Result<()> {
+        let mut events = vec![];
+        let mut index_stream = vec![];
+
+        // the first code added to the table stands for 2 indices, 1 past the
+        // limit of 3
+        LzwDecoder::default().decompress_with(
+            [DATA.as_slice()],
+            2,
+            2,
+            3,
+            &mut index_stream,
+            |bit, event| events.push((bit, event)),
+        )?;
+
+        assert_eq!(index_stream, [1, 1, 1, 1]);
+        assert_eq!(events, [(9, LzwEvent::ExtraIndices)]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use crate::error::{error, Result};
+use crate::grammar::TRANSPARENT;
+
+/// The layout of pixels written into caller-provided buffers.
+///
+/// Frames are composited as `0x00RRGGBB` pixels, the format `minifb` expects.
+/// A PixelFormat converts them while writing, so callers don't need a second
+/// conversion pass.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// 4 bytes per pixel, in R, G, B, A order.
+    #[default]
+    Rgba8,
+
+    /// 4 bytes per pixel, in B, G, R, A order.
+    Bgra8,
+
+    /// 3 bytes per pixel, in R, G, B order. Transparent pixels are written as
+    /// black.
+    Rgb8,
+
+    /// 4 bytes per pixel, in R, G, B, A order, with the color channels
+    /// multiplied by alpha.
+    ///
+    /// GIF transparency is all or nothing, so this only differs from
+    /// [`Rgba8`](PixelFormat::Rgba8) in name. It is provided so callers
+    /// targeting premultiplied surfaces don't have to special-case GIFs.
+    PremultipliedRgba8,
+
+    /// 1 byte per pixel, holding the luma of the pixel. Transparent pixels are
+    /// written as black.
+    Gray8,
+}
+
+impl PixelFormat {
+    pub const fn bytes_per_pixel(&self) -> usize {
+        match self {
+            Self::Rgba8 | Self::Bgra8 | Self::PremultipliedRgba8 => 4,
+            Self::Rgb8 => 3,
+            Self::Gray8 => 1,
+        }
+    }
+
+    /// Converts a row of `0x00RRGGBB` pixels into this format.
+    pub fn write(&self, pixels: &[u32], out: &mut [u8]) -> Result<()> {
+        let bytes_per_pixel = self.bytes_per_pixel();
+
+        if out.len() < pixels.len() * bytes_per_pixel {
+            return Err(error!(
+                "Output buffer is too small. Want to write {} bytes, but buffer is {} long.",
+                pixels.len() * bytes_per_pixel,
+                out.len()
+            ));
+        }
+
+        for (pixel, out) in pixels.iter().zip(out.chunks_exact_mut(bytes_per_pixel)) {
+            self.write_pixel(*pixel, out);
+        }
+
+        Ok(())
+    }
+
+    fn write_pixel(&self, pixel: u32, out: &mut [u8]) {
+        let [_, r, g, b] = pixel.to_be_bytes();
+        let (r, g, b, a) = if pixel == TRANSPARENT {
+            (0, 0, 0, 0)
+        } else {
+            (r, g, b, u8::MAX)
+        };
+
+        match self {
+            Self::Rgba8 | Self::PremultipliedRgba8 => out.copy_from_slice(&[r, g, b, a]),
+            Self::Bgra8 => out.copy_from_slice(&[b, g, r, a]),
+            Self::Rgb8 => out.copy_from_slice(&[r, g, b]),
+            Self::Gray8 => {
+                // ITU-R BT.601 luma
+                let luma = (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000;
+                out[0] = luma as u8;
+            }
+        }
+    }
+}
+
+/// Parses a color written as `RRGGBB`, optionally prefixed by `#`, into a
+/// `0x00RRGGBB` pixel.
+pub fn parse_color(color: &str) -> Result<u32> {
+    let hex = color.strip_prefix('#').unwrap_or(color);
+
+    // `from_str_radix` also accepts a leading sign
+    if hex.len() != 6 || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(error!("expected a color as `RRGGBB`, got `{}`", color));
+    }
+
+    u32::from_str_radix(hex, 16).map_err(|_| error!("invalid color `{}`", color))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write() -> Result<()> {
+        let pixels = [0x00FF_8000, TRANSPARENT];
+
+        let mut out = [0; 8];
+        PixelFormat::Rgba8.write(&pixels, &mut out)?;
+        assert_eq!(out, [0xFF, 0x80, 0x00, 0xFF, 0, 0, 0, 0]);
+
+        PixelFormat::Bgra8.write(&pixels, &mut out)?;
+        assert_eq!(out, [0x00, 0x80, 0xFF, 0xFF, 0, 0, 0, 0]);
+
+        let mut out = [0; 6];
+        PixelFormat::Rgb8.write(&pixels, &mut out)?;
+        assert_eq!(out, [0xFF, 0x80, 0x00, 0, 0, 0]);
+
+        let mut out = [0; 2];
+        PixelFormat::Gray8.write(&pixels, &mut out)?;
+        assert_eq!(out, [151, 0]);
+
+        assert!(PixelFormat::Rgba8.write(&pixels, &mut out).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn color() {
+        assert_eq!(parse_color("#FF8000"), Ok(0xFF_8000));
+        assert_eq!(parse_color("00ff00"), Ok(0x00_FF00));
+        assert!(parse_color("#FFF").is_err());
+        assert!(parse_color("GGGGGG").is_err());
+        assert!(parse_color("+FFFFF").is_err());
+    }
+}
@@ -0,0 +1,359 @@
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::delay::DelayPolicy;
+use crate::error::{error, Result};
+use crate::grammar::Frame;
+use crate::pixel_format::PixelFormat;
+
+/// Width, in font pixels, of a glyph in the annotation font, not counting the
+/// pixel of space after it.
+const GLYPH_WIDTH: usize = 3;
+const GLYPH_HEIGHT: usize = 5;
+
+/// Size, in sheet pixels, of a single font pixel.
+const FONT_SCALE: usize = 2;
+
+/// Space, in sheet pixels, around the annotation of a frame.
+const LABEL_PADDING: usize = 2;
+
+/// How a contact sheet lays out its frames.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SheetOptions {
+    /// Number of frames per row. Defaults to a roughly square grid.
+    pub columns: Option<usize>,
+
+    /// Space, in pixels, between frames and around the edge of the sheet.
+    pub spacing: usize,
+
+    /// Factor every frame is resized by, using nearest-neighbor sampling.
+    pub scale: f32,
+
+    /// Color of the space between frames, as a `0x00RRGGBB` pixel.
+    pub background: u32,
+
+    /// Writes the index and delay of each frame underneath it.
+    pub annotate: bool,
+
+    /// How the annotated delays are computed.
+    pub delay_policy: DelayPolicy,
+}
+
+impl Default for SheetOptions {
+    fn default() -> Self {
+        Self {
+            columns: None,
+            spacing: 0,
+            scale: 1.0,
+            background: 0,
+            annotate: false,
+            delay_policy: DelayPolicy::default(),
+        }
+    }
+}
+
+/// Where a frame was placed on a [`Sheet`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SheetFrame {
+    /// Position of the frame in the data stream.
+    pub index: usize,
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    pub delay: Duration,
+}
+
+/// Composited frames tiled into a single image, row by row.
+#[derive(Debug)]
+pub struct Sheet {
+    pub width: usize,
+    pub height: usize,
+
+    /// Pixels of the sheet, in `0x00RRGGBB` format.
+    pub pixels: Vec<u32>,
+
+    /// The rectangle each frame occupies, excluding its annotation.
+    pub frames: Vec<SheetFrame>,
+}
+
+impl Sheet {
+    /// Lays out composited frames, each `frame_width` by `frame_height`
+    /// pixels, in a grid.
+    pub fn new(
+        frames: &[Frame],
+        frame_width: usize,
+        frame_height: usize,
+        options: &SheetOptions,
+    ) -> Result<Self> {
+        let SheetOptions {
+            columns,
+            spacing,
+            scale,
+            background,
+            annotate,
+            delay_policy,
+        } = *options;
+
+        if frames.is_empty() {
+            return Err(error!("Can not lay out a contact sheet without frames."));
+        }
+
+        if frame_width == 0 || frame_height == 0 {
+            return Err(error!(
+                "Can not lay out a contact sheet of {}x{} pixel frames.",
+                frame_width, frame_height
+            ));
+        }
+
+        if !(scale > 0.0 && scale.is_finite()) {
+            return Err(error!("Scale must be positive. Got {}.", scale));
+        }
+
+        let columns = columns
+            .unwrap_or_else(|| frames.len().isqrt() + usize::from(!is_square(frames.len())))
+            .clamp(1, frames.len());
+        let rows = frames.len().div_ceil(columns);
+
+        let width = scaled(frame_width, scale);
+        let height = scaled(frame_height, scale);
+        let label_height = match annotate {
+            true => GLYPH_HEIGHT * FONT_SCALE + 2 * LABEL_PADDING,
+            false => 0,
+        };
+
+        let cell_width = width.checked_add(spacing);
+        let cell_height = height
+            .checked_add(label_height)
+            .and_then(|h| h.checked_add(spacing));
+
+        let sheet_width = cell_width
+            .and_then(|w| w.checked_mul(columns))
+            .and_then(|w| w.checked_add(spacing));
+        let sheet_height = cell_height
+            .and_then(|h| h.checked_mul(rows))
+            .and_then(|h| h.checked_add(spacing));
+
+        let (cell_width, cell_height, sheet_width, sheet_height) =
+            match (cell_width, cell_height, sheet_width, sheet_height) {
+                (Some(cw), Some(ch), Some(w), Some(h)) if w.checked_mul(h).is_some() => {
+                    (cw, ch, w, h)
+                }
+                _ => return Err(error!("Contact sheet is too large.")),
+            };
+
+        let mut sheet = Self {
+            width: sheet_width,
+            height: sheet_height,
+            pixels: vec![background; sheet_width * sheet_height],
+            frames: Vec::with_capacity(frames.len()),
+        };
+
+        for (index, frame) in frames.iter().enumerate() {
+            if frame.pixels.len() != frame_width * frame_height {
+                return Err(error!(
+                    "Frame {} has {} pixels, expected {}.",
+                    index,
+                    frame.pixels.len(),
+                    frame_width * frame_height
+                ));
+            }
+
+            let x = spacing + (index % columns) * cell_width;
+            let y = spacing + (index / columns) * cell_height;
+
+            for row in 0..height {
+                let source_row = row * frame_height / height * frame_width;
+                let target_row = (y + row) * sheet_width + x;
+
+                for column in 0..width {
+                    sheet.pixels[target_row + column] =
+                        frame.pixels[source_row + column * frame_width / width];
+                }
+            }
+
+            let delay = frame.delay(delay_policy);
+
+            if annotate {
+                let label = format!("#{} {}ms", index, delay.as_millis());
+                sheet.draw_text(&label, x, y + height + LABEL_PADDING, width, background);
+            }
+
+            sheet.frames.push(SheetFrame {
+                index,
+                x,
+                y,
+                width,
+                height,
+                delay,
+            });
+        }
+
+        Ok(sheet)
+    }
+
+    /// Writes the pixels of this sheet into `out` in the given
+    /// [`PixelFormat`].
+    pub fn write_pixels(&self, format: PixelFormat, out: &mut [u8]) -> Result<()> {
+        format.write(&self.pixels, out)
+    }
+
+    // draws text in a color that contrasts with the background, clipped to
+    // `max_width` pixels
+    fn draw_text(&mut self, text: &str, x: usize, y: usize, max_width: usize, background: u32) {
+        let [_, r, g, b] = background.to_be_bytes();
+        let luma = (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000;
+        let color = if luma > 0x80 { 0x00_0000 } else { 0xFF_FFFF };
+
+        let advance = (GLYPH_WIDTH + 1) * FONT_SCALE;
+
+        for (position, c) in text.chars().enumerate() {
+            let glyph_x = x + position * advance;
+
+            for (row, bits) in glyph(c).iter().enumerate() {
+                for column in 0..GLYPH_WIDTH {
+                    if bits & (0b100 >> column) == 0 {
+                        continue;
+                    }
+
+                    for dy in 0..FONT_SCALE {
+                        for dx in 0..FONT_SCALE {
+                            let px = glyph_x + column * FONT_SCALE + dx;
+                            let py = y + row * FONT_SCALE + dy;
+
+                            if px < x + max_width && px < self.width && py < self.height {
+                                self.pixels[py * self.width + px] = color;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+const fn is_square(n: usize) -> bool {
+    n.isqrt() * n.isqrt() == n
+}
+
+fn scaled(len: usize, scale: f32) -> usize {
+    ((len as f32 * scale) as usize).max(1)
+}
+
+// rows of a 3x5 glyph, most significant bit on the left
+const fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '#' => [0b101, 0b111, 0b101, 0b111, 0b101],
+        'm' => [0b000, 0b000, 0b111, 0b111, 0b101],
+        's' => [0b000, 0b011, 0b110, 0b011, 0b110],
+        _ => [0; GLYPH_HEIGHT],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(color: u32) -> Frame {
+        Frame {
+            delay_time: Some(10),
+            pixels: vec![color; 4 * 2],
+        }
+    }
+
+    #[test]
+    fn layout() -> Result<()> {
+        let frames = [frame(1), frame(2), frame(3), frame(4), frame(5)];
+        let options = SheetOptions {
+            spacing: 1,
+            scale: 0.5,
+            background: 0xFF_FFFF,
+            ..SheetOptions::default()
+        };
+
+        let sheet = Sheet::new(&frames, 4, 2, &options)?;
+
+        // 5 frames lay out as 3 columns and 2 rows of 2x1 frames
+        assert_eq!((sheet.width, sheet.height), (3 * 3 + 1, 2 * 2 + 1));
+        assert_eq!(sheet.frames[4].x, 1 + 3);
+        assert_eq!(sheet.frames[4].y, 1 + 2);
+
+        for (sheet_frame, frame) in sheet.frames.iter().zip(&frames) {
+            let SheetFrame {
+                x,
+                y,
+                width,
+                height,
+                ..
+            } = *sheet_frame;
+
+            for row in y..y + height {
+                let pixels = &sheet.pixels[row * sheet.width + x..][..width];
+                assert!(pixels.iter().all(|pixel| *pixel == frame.pixels[0]));
+            }
+        }
+
+        assert_eq!(sheet.pixels[0], 0xFF_FFFF);
+        assert_eq!(sheet.pixels.last(), Some(&0xFF_FFFF));
+
+        Ok(())
+    }
+
+    #[test]
+    fn annotate() -> Result<()> {
+        let options = SheetOptions {
+            annotate: true,
+            ..SheetOptions::default()
+        };
+
+        let sheet = Sheet::new(&[frame(0)], 4, 2, &options)?;
+        let label = &sheet.pixels[2 * sheet.width..];
+
+        assert_eq!(
+            sheet.height,
+            2 + GLYPH_HEIGHT * FONT_SCALE + 2 * LABEL_PADDING
+        );
+        assert!(label.contains(&0xFF_FFFF));
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Sheet::new(&[], 4, 2, &SheetOptions::default()).is_err());
+        assert!(Sheet::new(&[frame(0)], 3, 2, &SheetOptions::default()).is_err());
+
+        let empty = || Frame {
+            delay_time: None,
+            pixels: vec![],
+        };
+        assert!(Sheet::new(&[empty()], 0, 0, &SheetOptions::default()).is_err());
+        assert!(Sheet::new(&[empty()], 4, 0, &SheetOptions::default()).is_err());
+
+        let options = SheetOptions {
+            scale: 0.0,
+            ..SheetOptions::default()
+        };
+        assert!(Sheet::new(&[frame(0)], 4, 2, &options).is_err());
+
+        // the scaled frame size saturates, and must not overflow with spacing
+        let options = SheetOptions {
+            scale: 1e30,
+            spacing: 1,
+            ..SheetOptions::default()
+        };
+        assert!(Sheet::new(&[frame(0)], 4, 2, &options).is_err());
+    }
+}
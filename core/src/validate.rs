@@ -0,0 +1,751 @@
+//! Checks a GIF data stream against the grammar of the GIF89a specification.
+//!
+//! Unlike the [`Decoder`](crate::Decoder), which stops at the first problem it
+//! can not recover from and tolerates the rest, the validator reports every
+//! deviation it finds, so that the encoder of a broken GIF can be told what
+//! to fix.
+
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::decode::GIF87A_EXTENSION;
+use crate::diagnostic::Diagnostic;
+use crate::grammar::label::{
+    APPLICATION_EXTENSION, COMMENT_EXTENSION, EXTENSION, GRAPHIC_CONTROL_EXTENSION,
+    IMAGE_DESCRIPTOR, PLAIN_TEXT_EXTENSION, TRAILER,
+};
+use crate::lzw::{LzwDecoder, LzwEvent};
+use crate::version::Version;
+
+/// Byte offset of the Logical Screen Descriptor.
+const LOGICAL_SCREEN_OFFSET: usize = 6;
+
+/// Checks `data` against the GIF grammar, returning every deviation found,
+/// in the order they appear in the data stream.
+pub fn validate(data: &[u8]) -> Vec<Diagnostic> {
+    let mut validator = Validator {
+        data,
+        cursor: 0,
+        block: 0,
+        diagnostics: vec![],
+        version: Version::Gif89a,
+        canvas: (0, 0),
+        global_color_table_len: None,
+        pending_gce: None,
+        rendering_blocks: 0,
+        lzw_decoder: LzwDecoder::default(),
+        index_stream: vec![],
+    };
+
+    if validator.run().is_err() {
+        let block = validator.block;
+        validator.error(block, "The data stream ends in the middle of this block.");
+    }
+
+    validator.diagnostics
+}
+
+/// Marks that the data stream ended in the middle of a block.
+struct Truncated;
+
+type Step<T = ()> = Result<T, Truncated>;
+
+/// A Graphic Control Extension waiting for the graphic rendering block it
+/// applies to.
+struct PendingGce {
+    offset: usize,
+    transparent_color_index: Option<u8>,
+}
+
+/// A data sub-block, and the offset of its first byte in the data stream.
+struct SubBlock<'a> {
+    offset: usize,
+    data: &'a [u8],
+}
+
+struct Validator<'a> {
+    data: &'a [u8],
+    cursor: usize,
+
+    /// Offset of the block being validated.
+    block: usize,
+    diagnostics: Vec<Diagnostic>,
+    version: Version,
+    canvas: (u16, u16),
+    global_color_table_len: Option<usize>,
+    pending_gce: Option<PendingGce>,
+    rendering_blocks: usize,
+    lzw_decoder: LzwDecoder,
+    index_stream: Vec<u8>,
+}
+
+impl<'a> Validator<'a> {
+    fn run(&mut self) -> Step {
+        if !self.data.starts_with(b"GIF") {
+            self.error(
+                0,
+                "The data stream does not start with the `GIF` signature.",
+            );
+            return Ok(());
+        }
+
+        self.cursor = 3;
+        self.version = Version::from_bytes([self.byte()?, self.byte()?, self.byte()?]);
+
+        if let Version::Unknown(_) = self.version {
+            let message = format!("Unknown version GIF{}.", self.version);
+            self.error(3, message);
+        }
+
+        self.logical_screen()?;
+
+        loop {
+            if self.cursor == self.data.len() {
+                self.warning(self.cursor, "The data stream ends without a trailer.");
+                break;
+            }
+
+            self.block = self.cursor;
+
+            match self.byte()? {
+                EXTENSION => self.extension()?,
+                IMAGE_DESCRIPTOR => self.image()?,
+                TRAILER => {
+                    let trailing = self.data.len() - self.cursor;
+
+                    if trailing > 0 {
+                        let message = format!("{} bytes of data follow the trailer.", trailing);
+                        self.warning(self.cursor, message);
+                    }
+
+                    break;
+                }
+                byte => {
+                    let message =
+                        format!("Unexpected byte {:#04x} where a block should start.", byte);
+                    self.error(self.block, message);
+                    return Ok(());
+                }
+            }
+        }
+
+        if let Some(gce) = self.pending_gce.take() {
+            self.warning(
+                gce.offset,
+                "This Graphic Control Extension is not followed by a graphic rendering block.",
+            );
+        }
+
+        if self.rendering_blocks == 0 {
+            self.warning(self.block, "The data stream contains no images.");
+        }
+
+        Ok(())
+    }
+
+    fn logical_screen(&mut self) -> Step {
+        self.block = LOGICAL_SCREEN_OFFSET;
+
+        let canvas_width = self.u16()?;
+        let canvas_height = self.u16()?;
+        let packed_field = self.byte()?;
+        let background_color_index = self.byte()?;
+        let _pixel_aspect_ratio = self.byte()?;
+
+        self.canvas = (canvas_width, canvas_height);
+
+        if canvas_width == 0 || canvas_height == 0 {
+            let message = format!(
+                "The logical screen is {}x{} pixels, so nothing can be displayed.",
+                canvas_width, canvas_height
+            );
+            self.warning(LOGICAL_SCREEN_OFFSET, message);
+        }
+
+        if packed_field & 0b1000_0000 != 0 {
+            let len = color_table_len(packed_field);
+            self.slice(3 * len)?;
+            self.global_color_table_len = Some(len);
+
+            if background_color_index as usize >= len {
+                let message = format!(
+                    "Background color index {} is out of range for a Global Color Table of {} colors.",
+                    background_color_index, len
+                );
+                self.warning(LOGICAL_SCREEN_OFFSET, message);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn extension(&mut self) -> Step {
+        let offset = self.block;
+        let label = self.byte()?;
+
+        if self.version == Version::Gif87a {
+            self.warning(offset, GIF87A_EXTENSION);
+        }
+
+        match label {
+            GRAPHIC_CONTROL_EXTENSION => {
+                if let Some(gce) = &self.pending_gce {
+                    let message = format!(
+                        "A second Graphic Control Extension precedes a single graphic rendering block, after the one at {:#010x}.",
+                        gce.offset
+                    );
+                    self.error(offset, message);
+                }
+
+                let block_size = self.byte()?;
+                let block = self.slice(block_size as usize)?;
+
+                if block_size != 4 {
+                    let message = format!(
+                        "The block size of a Graphic Control Extension must be 4, but is {}.",
+                        block_size
+                    );
+                    self.error(offset, message);
+                }
+
+                let mut transparent_color_index = None;
+
+                if let [packed_field, _, _, index, ..] = *block {
+                    let disposal_method = (packed_field >> 2) & 0b111;
+
+                    if disposal_method > 3 {
+                        let message = format!("Disposal method {} is reserved.", disposal_method);
+                        self.warning(offset, message);
+                    }
+
+                    if packed_field & 1 != 0 {
+                        transparent_color_index = Some(index);
+                    }
+                }
+
+                self.terminator("Graphic Control Extension")?;
+
+                self.pending_gce = Some(PendingGce {
+                    offset,
+                    transparent_color_index,
+                });
+            }
+            PLAIN_TEXT_EXTENSION => {
+                let block_size = self.byte()?;
+                self.slice(block_size as usize)?;
+
+                if block_size != 12 {
+                    let message = format!(
+                        "The block size of a Plain Text Extension must be 12, but is {}.",
+                        block_size
+                    );
+                    self.error(offset, message);
+                }
+
+                if self.global_color_table_len.is_none() {
+                    self.warning(
+                        offset,
+                        "Plain Text Extensions are drawn with the Global Color Table, but there is none.",
+                    );
+                }
+
+                self.sub_blocks()?;
+                self.pending_gce = None;
+                self.rendering_blocks += 1;
+            }
+            APPLICATION_EXTENSION => {
+                self.interrupts_gce(offset);
+
+                let block_size = self.byte()?;
+                self.slice(block_size as usize)?;
+
+                if block_size != 11 {
+                    let message = format!(
+                        "The block size of an Application Extension must be 11, but is {}.",
+                        block_size
+                    );
+                    self.error(offset, message);
+                }
+
+                self.sub_blocks()?;
+            }
+            COMMENT_EXTENSION => {
+                self.interrupts_gce(offset);
+                self.sub_blocks()?;
+            }
+            label => {
+                let message = format!("Unknown extension label {:#04x}.", label);
+                self.warning(offset, message);
+                self.sub_blocks()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn image(&mut self) -> Step {
+        let offset = self.block;
+
+        let left = self.u16()?;
+        let top = self.u16()?;
+        let width = self.u16()?;
+        let height = self.u16()?;
+        let packed_field = self.byte()?;
+
+        let gce = self.pending_gce.take();
+        self.rendering_blocks += 1;
+
+        if width == 0 || height == 0 {
+            let message = format!(
+                "The image is {}x{} pixels, so it has no pixels.",
+                width, height
+            );
+            self.warning(offset, message);
+        }
+
+        let (canvas_width, canvas_height) = self.canvas;
+
+        if left as u32 + width as u32 > canvas_width as u32
+            || top as u32 + height as u32 > canvas_height as u32
+        {
+            let message = format!(
+                "The image of {}x{} pixels at ({}, {}) extends past the {}x{} logical screen.",
+                width, height, left, top, canvas_width, canvas_height
+            );
+            self.warning(offset, message);
+        }
+
+        let local_color_table_len = match packed_field & 0b1000_0000 != 0 {
+            true => {
+                let len = color_table_len(packed_field);
+                self.slice(3 * len)?;
+                Some(len)
+            }
+            false => None,
+        };
+
+        let color_table_len = local_color_table_len.or(self.global_color_table_len);
+
+        if color_table_len.is_none() {
+            self.error(
+                offset,
+                "The image has no Local Color Table, and there is no Global Color Table.",
+            );
+        }
+
+        if let (Some(gce), Some(len)) = (&gce, color_table_len) {
+            if let Some(index) = gce
+                .transparent_color_index
+                .filter(|index| *index as usize >= len)
+            {
+                let message = format!(
+                    "Transparent color index {} is out of range for a color table of {} colors.",
+                    index, len
+                );
+                self.warning(gce.offset, message);
+            }
+        }
+
+        let lzw_minimum_code_offset = self.cursor;
+        let lzw_minimum_code = self.byte()?;
+        let sub_blocks = self.sub_blocks()?;
+
+        match lzw_minimum_code {
+            2..=8 => {}
+            1 | 9..=11 => {
+                let message = format!(
+                    "LZW minimum code size {} is outside of the range of 2 to 8.",
+                    lzw_minimum_code
+                );
+                self.warning(lzw_minimum_code_offset, message);
+            }
+            _ => {
+                let message = format!("Invalid LZW minimum code size {}.", lzw_minimum_code);
+                self.error(lzw_minimum_code_offset, message);
+                return Ok(());
+            }
+        }
+
+        self.image_data(
+            offset,
+            width,
+            height,
+            color_table_len,
+            lzw_minimum_code,
+            &sub_blocks,
+        );
+
+        Ok(())
+    }
+
+    // decodes the image data, reporting problems at the byte of the code they
+    // were found at
+    fn image_data(
+        &mut self,
+        offset: usize,
+        width: u16,
+        height: u16,
+        color_table_len: Option<usize>,
+        lzw_minimum_code: u8,
+        sub_blocks: &[SubBlock<'_>],
+    ) {
+        let diagnostics = &mut self.diagnostics;
+        let pixels = width as usize * height as usize;
+
+        // a missing color table is reported with the image descriptor
+        let color_table_len = color_table_len.unwrap_or(usize::MAX);
+
+        let decompressed = self.lzw_decoder.decompress_with(
+            sub_blocks.iter().map(|sub_block| sub_block.data),
+            lzw_minimum_code,
+            color_table_len,
+            pixels,
+            &mut self.index_stream,
+            |bit, event| {
+                let offset = offset_of(sub_blocks, bit).unwrap_or(offset);
+
+                let diagnostic = match event {
+                    LzwEvent::MissingClearCode => Diagnostic::warning(
+                        offset,
+                        "The image data does not start with a Clear code.",
+                    ),
+                    LzwEvent::UnknownCode { code, table_len } => Diagnostic::error(
+                        offset,
+                        format!(
+                            "LZW code {} is beyond the code table, which holds {} codes.",
+                            code, table_len
+                        ),
+                    ),
+                    LzwEvent::IndexOutOfRange { index } => Diagnostic::error(
+                        offset,
+                        format!(
+                            "Color index {} is out of range for a color table of {} colors.",
+                            index, color_table_len
+                        ),
+                    ),
+                    LzwEvent::ExtraIndices => Diagnostic::warning(
+                        offset,
+                        format!(
+                            "The image data holds more than the {} pixels the image has.",
+                            pixels
+                        ),
+                    ),
+                    LzwEvent::DataAfterEnd => Diagnostic::warning(
+                        offset,
+                        "Non-zero data follows the End of Information code.",
+                    ),
+                    LzwEvent::MissingEnd => Diagnostic::warning(
+                        offset,
+                        "The image data ends without an End of Information code.",
+                    ),
+                };
+
+                diagnostics.push(diagnostic);
+            },
+        );
+
+        // the decoder reports why it stopped, and the pixels it did decode
+        // don't say anything more
+        if decompressed.is_err() {
+            return;
+        }
+
+        // image data that is too long is reported where it goes past the end
+        if self.index_stream.len() < pixels {
+            let message = format!(
+                "The image data holds {} pixels, but the image has {}.",
+                self.index_stream.len(),
+                pixels
+            );
+            self.warning(offset, message);
+        }
+    }
+
+    // special-purpose blocks may not sit between a Graphic Control Extension
+    // and the block it applies to
+    fn interrupts_gce(&mut self, offset: usize) {
+        if let Some(gce) = &self.pending_gce {
+            let message = format!(
+                "This block separates the Graphic Control Extension at {:#010x} from its graphic rendering block.",
+                gce.offset
+            );
+            self.warning(offset, message);
+        }
+    }
+
+    // reads data sub-blocks up to and including the block terminator
+    fn sub_blocks(&mut self) -> Step<Vec<SubBlock<'a>>> {
+        let mut sub_blocks = vec![];
+
+        loop {
+            let size = self.byte()? as usize;
+
+            if size == 0 {
+                return Ok(sub_blocks);
+            }
+
+            let offset = self.cursor;
+            let data = self.slice(size)?;
+            sub_blocks.push(SubBlock { offset, data });
+        }
+    }
+
+    // reads the block terminator of a block that has no data sub-blocks
+    fn terminator(&mut self, block: &str) -> Step {
+        let offset = self.cursor;
+        let len = self
+            .sub_blocks()?
+            .iter()
+            .map(|sub_block| sub_block.data.len())
+            .sum::<usize>();
+
+        if len > 0 {
+            let message = format!(
+                "{} bytes of data sub-blocks follow the {}, which should end with a block terminator.",
+                len, block
+            );
+            self.error(offset, message);
+        }
+
+        Ok(())
+    }
+
+    fn byte(&mut self) -> Step<u8> {
+        let byte = *self.data.get(self.cursor).ok_or(Truncated)?;
+        self.cursor += 1;
+        Ok(byte)
+    }
+
+    fn u16(&mut self) -> Step<u16> {
+        Ok(u16::from_le_bytes([self.byte()?, self.byte()?]))
+    }
+
+    fn slice(&mut self, len: usize) -> Step<&'a [u8]> {
+        let data = self.data;
+        let slice = data.get(self.cursor..self.cursor + len).ok_or(Truncated)?;
+        self.cursor += len;
+        Ok(slice)
+    }
+
+    fn warning(&mut self, offset: usize, message: impl Into<alloc::string::String>) {
+        self.diagnostics.push(Diagnostic::warning(offset, message));
+    }
+
+    fn error(&mut self, offset: usize, message: impl Into<alloc::string::String>) {
+        self.diagnostics.push(Diagnostic::error(offset, message));
+    }
+}
+
+// maps a bit of the image data to the offset of its byte in the data stream,
+// skipping over the sizes of the sub-blocks. Bits past the end map to the
+// byte after the last sub-block.
+fn offset_of(sub_blocks: &[SubBlock<'_>], bit: usize) -> Option<usize> {
+    let mut byte = bit / 8;
+
+    for sub_block in sub_blocks {
+        if byte < sub_block.data.len() {
+            return Some(sub_block.offset + byte);
+        }
+
+        byte -= sub_block.data.len();
+    }
+
+    sub_blocks
+        .last()
+        .map(|sub_block| sub_block.offset + sub_block.data.len())
+}
+
+// number of colors in a color table, from the size in a packed field
+const fn color_table_len(packed_field: u8) -> usize {
+    1 << ((packed_field & 0b111) + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostic::Severity;
+
+    use super::*;
+
+    // packs LZW codes, given as (code, length in bits), into bytes
+    fn pack(codes: &[(usize, usize)]) -> Vec<u8> {
+        let mut bytes = vec![];
+        let mut bit = 0;
+
+        for &(code, len) in codes {
+            for i in 0..len {
+                if bit / 8 == bytes.len() {
+                    bytes.push(0);
+                }
+
+                bytes[bit / 8] |= (((code >> i) & 1) as u8) << (bit % 8);
+                bit += 1;
+            }
+        }
+
+        bytes
+    }
+
+    // the four 0 indices of a 2x2 image, with a minimum code size of 2
+    fn image_data() -> Vec<u8> {
+        pack(&[(4, 3), (0, 3), (0, 3), (0, 3), (0, 4), (5, 4)])
+    }
+
+    fn image(left: u16, lzw_data: &[u8]) -> Vec<u8> {
+        let mut image = vec![IMAGE_DESCRIPTOR];
+        image.extend(left.to_le_bytes());
+        image.extend([0, 0, 2, 0, 2, 0, 0, 2, lzw_data.len() as u8]);
+        image.extend(lzw_data);
+        image.push(0);
+        image
+    }
+
+    const GCE: [u8; 8] = [EXTENSION, GRAPHIC_CONTROL_EXTENSION, 4, 0, 10, 0, 0, 0];
+
+    // a 2x2 GIF with a black and white Global Color Table
+    fn gif(blocks: &[&[u8]]) -> Vec<u8> {
+        let mut gif = b"GIF89a".to_vec();
+        gif.extend([2, 0, 2, 0, 0b1000_0000, 0, 0]);
+        gif.extend([0, 0, 0, 0xFF, 0xFF, 0xFF]);
+
+        for block in blocks {
+            gif.extend(*block);
+        }
+
+        gif
+    }
+
+    fn messages(data: &[u8]) -> Vec<(Severity, usize, alloc::string::String)> {
+        validate(data)
+            .into_iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.offset, diagnostic.message))
+            .collect()
+    }
+
+    #[test]
+    fn valid() {
+        let image = image(0, &image_data());
+        assert_eq!(messages(&gif(&[&GCE, &image, &[TRAILER]])), []);
+    }
+
+    #[test]
+    fn trailer() {
+        let image = image(0, &image_data());
+
+        let missing = gif(&[&image]);
+        let diagnostics = messages(&missing);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].1, missing.len());
+
+        let garbage = gif(&[&image, &[TRAILER, 1, 2, 3]]);
+        let diagnostics = messages(&garbage);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].1, garbage.len() - 3);
+    }
+
+    #[test]
+    fn graphic_control_extensions() {
+        let image = image(0, &image_data());
+        let long_gce = [EXTENSION, GRAPHIC_CONTROL_EXTENSION, 5, 0, 10, 0, 0, 0, 0];
+
+        let diagnostics = messages(&gif(&[&GCE, &long_gce, &image, &[TRAILER]]));
+        let offset = 19 + GCE.len();
+
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics
+            .iter()
+            .all(|(severity, at, _)| *severity == Severity::Error && *at == offset));
+    }
+
+    #[test]
+    fn outside_logical_screen() {
+        let image = image(1, &image_data());
+        let diagnostics = messages(&gif(&[&image, &[TRAILER]]));
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].0, Severity::Warning);
+        assert_eq!(diagnostics[0].1, 19);
+    }
+
+    #[test]
+    fn color_index_out_of_range() {
+        let data = pack(&[(4, 3), (3, 3), (0, 3), (0, 3), (0, 4), (5, 4)]);
+        let diagnostics = messages(&gif(&[&image(0, &data), &[TRAILER]]));
+
+        // reported at the code for index 3, in the first byte of image data
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].0, Severity::Error);
+        assert_eq!(diagnostics[0].1, 19 + 12);
+        assert!(diagnostics[0].2.contains("Color index 3"));
+    }
+
+    #[test]
+    fn code_beyond_table() {
+        let data = pack(&[(4, 3), (0, 3), (7, 3), (5, 3)]);
+        let diagnostics = messages(&gif(&[&image(0, &data), &[TRAILER]]));
+
+        // the code after the clear code and 0 starts in the first byte of
+        // image data, after the descriptor, minimum code size and block size
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].0, Severity::Error);
+        assert_eq!(diagnostics[0].1, 19 + 12);
+    }
+
+    #[test]
+    fn too_many_pixels() {
+        let codes = [(4, 3), (0, 3)].repeat(5);
+        let data = pack(&[codes.as_slice(), &[(5, 3)]].concat());
+        let diagnostics = messages(&gif(&[&image(0, &data), &[TRAILER]]));
+
+        // reported at the fifth 0, in the fourth byte of image data
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].0, Severity::Warning);
+        assert_eq!(diagnostics[0].1, 19 + 12 + 3);
+        assert!(diagnostics[0].2.contains("more than the 4 pixels"));
+    }
+
+    #[test]
+    fn data_after_end_of_information() {
+        let mut data = image_data();
+        data.push(0xFF);
+
+        let diagnostics = messages(&gif(&[&image(0, &data), &[TRAILER]]));
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].2.contains("End of Information"));
+    }
+
+    #[test]
+    fn version_and_truncation() {
+        let mut data = gif(&[&GCE, &image(0, &image_data()), &[TRAILER]]);
+        data[3..6].copy_from_slice(b"87a");
+        data.truncate(data.len() - 5);
+
+        let diagnostics = messages(&data);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].1, 19);
+        assert_eq!(
+            diagnostics[1],
+            (Severity::Error, 19 + GCE.len(), diagnostics[1].2.clone())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn sample_gifs() -> crate::Result<()> {
+        for sample in ["sample_1.gif", "sample_2_animation.gif", "lady-dance.gif"] {
+            let data = crate::dump_gif(&format!("../sample_gifs/{}", sample))?;
+            let diagnostics = validate(&data);
+
+            assert!(
+                diagnostics
+                    .iter()
+                    .all(|diagnostic| diagnostic.severity == Severity::Warning),
+                "{}: {:?}",
+                sample,
+                diagnostics
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use core::fmt;
+
+/// The version of the GIF specification a data stream claims to follow, as
+/// given by the three bytes after the `GIF` signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Version {
+    Gif87a,
+    Gif89a,
+
+    /// Any other version bytes.
+    Unknown([u8; 3]),
+}
+
+impl Version {
+    pub const fn from_bytes(bytes: [u8; 3]) -> Self {
+        match &bytes {
+            b"87a" => Self::Gif87a,
+            b"89a" => Self::Gif89a,
+            _ => Self::Unknown(bytes),
+        }
+    }
+
+    pub const fn to_bytes(self) -> [u8; 3] {
+        match self {
+            Self::Gif87a => *b"87a",
+            Self::Gif89a => *b"89a",
+            Self::Unknown(bytes) => bytes,
+        }
+    }
+}
+
+/// Writes the version as it appears after the `GIF` signature, e.g. `89a`.
+/// Bytes that aren't printable ASCII are written as `?`.
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.to_bytes() {
+            let c = match byte.is_ascii_graphic() {
+                true => char::from(byte),
+                false => '?',
+            };
+
+            write!(f, "{}", c)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// What the [`Decoder`](crate::Decoder) does when a data stream doesn't
+/// follow the version it claims.
+///
+/// That is, when its version is unknown, or when a GIF87a data stream
+/// contains extension blocks, which were introduced in GIF89a.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VersionPolicy {
+    /// Fail to parse the data stream.
+    Reject,
+
+    /// Parse the data stream, and report each deviation as a warning in
+    /// [`GifDataStream::diagnostics`](crate::gif_data_stream::GifDataStream::diagnostics).
+    /// Many encoders label GIF89a files as GIF87a, so this is the default.
+    #[default]
+    Warn,
+
+    /// Parse the data stream as if it were GIF89a.
+    Accept,
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn bytes() {
+        assert_eq!(Version::from_bytes(*b"87a"), Version::Gif87a);
+        assert_eq!(Version::from_bytes(*b"89a"), Version::Gif89a);
+        assert_eq!(Version::from_bytes(*b"12x"), Version::Unknown(*b"12x"));
+
+        assert_eq!(Version::Gif89a.to_string(), "89a");
+        assert_eq!(Version::Unknown([b'1', 0, b'x']).to_string(), "1?x");
+    }
+}
@@ -1,16 +1,17 @@
 #!/usr/bin/env bash
 
-if [ "$#" -ne 1 ]; then
-    echo "Usage: $0 <gif-file>"
+if [ "$#" -lt 1 ] || [ "$#" -gt 2 ]; then
+    echo "Usage: $0 <gif-file> [iterations]"
     exit 1
 fi
 
 GIF_FILE=$1
+ITERATIONS=${2:-100}
 
 if [ ! -f "$GIF_FILE" ]; then
     echo "Error: File '$GIF_FILE' not found!"
     exit 1
 fi
 
-cargo build --release
-samply record ./target/release/jif-render "$GIF_FILE"
\ No newline at end of file
+cargo build --release --bin jif-render-bench
+samply record ./target/release/jif-render-bench --iterations "$ITERATIONS" "$GIF_FILE"
\ No newline at end of file
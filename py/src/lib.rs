@@ -0,0 +1,353 @@
+use std::path::PathBuf;
+
+use jif::gif_data_stream::{Block, GifDataStream};
+use jif::grammar::DisposalMethod;
+use jif::{Decoder, DelayPolicy, FrameDecoder, FrameInfo, PixelFormat};
+use numpy::ndarray::Array4;
+use numpy::{IntoPyArray, PyArray4};
+use pyo3::create_exception;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+create_exception!(
+    jif,
+    GifError,
+    PyValueError,
+    "Raised when a GIF fails to parse or decode."
+);
+
+/// A parsed GIF data stream.
+#[pyclass(module = "jif", frozen)]
+struct Gif {
+    gif: GifDataStream,
+    frames: Vec<FrameInfo>,
+}
+
+#[pymethods]
+impl Gif {
+    /// Parses a GIF from its bytes.
+    #[new]
+    fn new(py: Python<'_>, data: Vec<u8>) -> PyResult<Self> {
+        py.detach(|| parse(data)).map_err(to_py_err)
+    }
+
+    /// The version of the data stream, usually "87a" or "89a".
+    #[getter]
+    fn version(&self) -> String {
+        self.gif.version.to_string()
+    }
+
+    /// Width of the canvas, in pixels.
+    #[getter]
+    const fn width(&self) -> u16 {
+        self.gif.logical_screen_descriptor.canvas_width
+    }
+
+    /// Height of the canvas, in pixels.
+    #[getter]
+    const fn height(&self) -> u16 {
+        self.gif.logical_screen_descriptor.canvas_height
+    }
+
+    #[getter]
+    const fn background_color_index(&self) -> u8 {
+        self.gif.logical_screen_descriptor.background_color_index
+    }
+
+    #[getter]
+    const fn pixel_aspect_ratio(&self) -> u8 {
+        self.gif.logical_screen_descriptor.pixel_aspect_ratio
+    }
+
+    /// The global color table as RGB triplets, if there is one.
+    #[getter]
+    fn global_color_table<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyBytes>> {
+        self.gif
+            .global_color_table
+            .as_ref()
+            .map(|color_table| PyBytes::new(py, color_table))
+    }
+
+    /// Describes every frame without decoding any image data.
+    #[getter]
+    fn frames(&self) -> PyResult<Vec<Frame>> {
+        self.frames
+            .iter()
+            .enumerate()
+            .map(|(index, frame)| Frame::new(index, frame, &self.gif))
+            .collect::<jif::Result<_>>()
+            .map_err(to_py_err)
+    }
+
+    /// Every comment extension in the data stream, in order.
+    #[getter]
+    fn comments(&self) -> Vec<Comment> {
+        self.blocks()
+            .filter_map(|(offset, block)| match block {
+                Block::CommentExtension(comment) => Some(Comment {
+                    offset,
+                    data: comment.data.clone(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Every application extension in the data stream, in order.
+    #[getter]
+    fn application_extensions(&self) -> Vec<ApplicationExtension> {
+        self.blocks()
+            .filter_map(|(offset, block)| match block {
+                Block::ApplicationExtension(extension) => Some(ApplicationExtension {
+                    offset,
+                    identifier: extension.identifier.clone(),
+                    authentication_code: extension.authentication_code.to_vec(),
+                    data: extension.data.clone(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Decodes every frame into a `uint8` array of shape
+    /// `(frames, height, width, 4)`, holding RGBA pixels.
+    ///
+    /// Unless `transparent_background` is set, pixels that no frame has
+    /// painted are the background color.
+    #[pyo3(signature = (transparent_background = false))]
+    fn decode<'py>(
+        &self,
+        py: Python<'py>,
+        transparent_background: bool,
+    ) -> PyResult<Bound<'py, PyArray4<u8>>> {
+        let width = self.width() as usize;
+        let height = self.height() as usize;
+        let shape = (self.frames.len(), height, width, 4);
+
+        let pixels = py
+            .detach(|| {
+                let mut frame_decoder =
+                    FrameDecoder::new(&self.gif)?.with_pixel_format(PixelFormat::Rgba8);
+
+                if transparent_background {
+                    frame_decoder = frame_decoder.with_transparent_background();
+                }
+
+                let mut pixels = vec![0; shape.0 * shape.1 * shape.2 * shape.3];
+
+                // an empty canvas has no pixels to decode into, and
+                // `chunks_exact_mut` panics on a chunk size of 0
+                if !pixels.is_empty() {
+                    for out in pixels.chunks_exact_mut(height * width * 4) {
+                        frame_decoder.next_frame_into(out)?;
+                    }
+                }
+
+                Ok(pixels)
+            })
+            .map_err(to_py_err)?;
+
+        let pixels = Array4::from_shape_vec(shape, pixels).expect("The buffer holds every frame.");
+
+        Ok(pixels.into_pyarray(py))
+    }
+
+    fn __len__(&self) -> usize {
+        self.frames.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Gif(version={:?}, width={}, height={}, frames={})",
+            self.version(),
+            self.width(),
+            self.height(),
+            self.frames.len()
+        )
+    }
+}
+
+impl Gif {
+    fn blocks(&self) -> impl Iterator<Item = (usize, &Block)> {
+        self.gif
+            .block_offsets
+            .iter()
+            .copied()
+            .zip(self.gif.blocks.iter())
+    }
+}
+
+/// Where a frame is drawn on the canvas, and how it is displayed.
+#[pyclass(module = "jif", frozen, get_all)]
+struct Frame {
+    /// Position of the frame in the data stream.
+    index: usize,
+
+    /// Byte offset of the frame's image descriptor.
+    offset: usize,
+
+    left: u16,
+    top: u16,
+    width: u16,
+    height: u16,
+
+    /// Delay time in hundredths of a second, or None if the frame has no
+    /// graphic control extension.
+    delay_time: Option<u16>,
+
+    /// How long the frame is displayed for, in milliseconds, matching how
+    /// browsers play GIFs back.
+    delay_ms: u64,
+
+    disposal_method: &'static str,
+    transparent_color_index: Option<u8>,
+    has_local_color_table: bool,
+
+    /// Whether the frame can be composited without any of the frames before it.
+    keyframe: bool,
+}
+
+impl Frame {
+    fn new(index: usize, frame: &FrameInfo, gif: &GifDataStream) -> jif::Result<Self> {
+        let table_based_image = frame.table_based_image(gif)?;
+        let image_descriptor = &table_based_image.image_descriptor;
+        let graphic_control_extension = frame.graphic_control_extension(gif)?;
+
+        let delay_time = graphic_control_extension.map(|gce| gce.delay_time);
+        let disposal_method = graphic_control_extension
+            .map_or(DisposalMethod::NotRequired, |gce| gce.disposal_method());
+
+        Ok(Self {
+            index,
+            offset: frame.offset,
+            left: image_descriptor.image_left,
+            top: image_descriptor.image_top,
+            width: image_descriptor.image_width,
+            height: image_descriptor.image_height,
+            delay_time,
+            delay_ms: DelayPolicy::BrowserCompatible
+                .duration(delay_time)
+                .as_millis() as u64,
+            disposal_method: disposal_method.as_str(),
+            transparent_color_index: graphic_control_extension
+                .filter(|gce| gce.transparent_color_flag())
+                .map(|gce| gce.transparent_color_index),
+            has_local_color_table: table_based_image.local_color_table.is_some(),
+            keyframe: frame.keyframe,
+        })
+    }
+}
+
+#[pymethods]
+impl Frame {
+    fn __repr__(&self) -> String {
+        format!(
+            "Frame(index={}, left={}, top={}, width={}, height={}, delay_ms={})",
+            self.index, self.left, self.top, self.width, self.height, self.delay_ms
+        )
+    }
+}
+
+/// A comment extension.
+#[pyclass(module = "jif", frozen)]
+struct Comment {
+    offset: usize,
+    data: Vec<u8>,
+}
+
+#[pymethods]
+impl Comment {
+    /// Byte offset of the extension in the data stream.
+    #[getter]
+    const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    #[getter]
+    fn data<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.data)
+    }
+
+    /// The comment decoded as text. Comments are meant to be 7-bit ASCII,
+    /// but invalid bytes are replaced rather than raising.
+    #[getter]
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.data).into_owned()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Comment({:?})", self.text())
+    }
+}
+
+/// An application extension, such as the NETSCAPE2.0 looping extension.
+#[pyclass(module = "jif", frozen)]
+struct ApplicationExtension {
+    offset: usize,
+    identifier: String,
+    authentication_code: Vec<u8>,
+    data: Vec<u8>,
+}
+
+#[pymethods]
+impl ApplicationExtension {
+    /// Byte offset of the extension in the data stream.
+    #[getter]
+    const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    #[getter]
+    fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    #[getter]
+    fn authentication_code<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.authentication_code)
+    }
+
+    #[getter]
+    fn data<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.data)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ApplicationExtension(identifier={:?}, authentication_code={:?})",
+            self.identifier,
+            String::from_utf8_lossy(&self.authentication_code)
+        )
+    }
+}
+
+/// Reads and parses a GIF file.
+#[pyfunction]
+fn open(py: Python<'_>, path: PathBuf) -> PyResult<Gif> {
+    let data = py.detach(|| std::fs::read(path))?;
+    Gif::new(py, data)
+}
+
+fn parse(data: Vec<u8>) -> jif::Result<Gif> {
+    let gif = Decoder::new(data).parse()?;
+    let frames = gif.frame_decoder()?.frames().to_vec();
+
+    Ok(Gif { gif, frames })
+}
+
+fn to_py_err(err: jif::Error) -> PyErr {
+    GifError::new_err(err.to_string())
+}
+
+#[pymodule]
+#[pyo3(name = "jif")]
+fn jif_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Gif>()?;
+    m.add_class::<Frame>()?;
+    m.add_class::<Comment>()?;
+    m.add_class::<ApplicationExtension>()?;
+    m.add_function(wrap_pyfunction!(open, m)?)?;
+    m.add("GifError", m.py().get_type::<GifError>())?;
+    Ok(())
+}
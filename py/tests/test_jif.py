@@ -0,0 +1,47 @@
+from pathlib import Path
+
+import pytest
+
+import jif
+
+SAMPLE_GIFS = Path(__file__).resolve().parents[2] / "sample_gifs"
+
+# a 0x0 logical screen holding a single 0x0 frame
+EMPTY = (
+    b"GIF89a\x00\x00\x00\x00\x80\x00\x00"
+    b"\x00\x00\x00\xff\xff\xff"
+    b"\x2c\x00\x00\x00\x00\x00\x00\x00\x00\x00"
+    b"\x02\x01\x2c\x00"
+    b"\x3b"
+)
+
+
+def test_open():
+    gif = jif.open(SAMPLE_GIFS / "sample_2_animation.gif")
+
+    assert gif.version == "89a"
+    assert len(gif) == len(gif.frames) > 1
+    assert gif.frames[0].keyframe
+    assert all(frame.disposal_method for frame in gif.frames)
+
+
+def test_decode():
+    gif = jif.open(SAMPLE_GIFS / "sample_2_animation.gif")
+    pixels = gif.decode()
+
+    assert pixels.shape == (len(gif), gif.height, gif.width, 4)
+    assert pixels.dtype == "uint8"
+
+
+def test_decode_empty_canvas():
+    gif = jif.Gif(EMPTY)
+
+    assert (gif.width, gif.height) == (0, 0)
+    assert gif.decode().shape == (len(gif), 0, 0, 4)
+
+
+def test_invalid():
+    with pytest.raises(jif.GifError):
+        jif.Gif(b"GIF89a")
+
+    assert issubclass(jif.GifError, ValueError)
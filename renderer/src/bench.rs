@@ -0,0 +1,229 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+use eyre::{eyre, Result};
+
+use jif::{dump_gif, Decoder};
+use jif_render::playlist::Playlist;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+    static PEAK: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Wraps the system allocator to track how much memory the current thread has
+/// allocated, and the most it allocated at once. Counting per thread keeps
+/// other threads, such as concurrently running tests, out of the peak. `jif`
+/// decodes on the calling thread, as this binary doesn't enable `rayon`.
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+
+        if !ptr.is_null() {
+            // the counters are gone while the thread is being torn down
+            let _ = ALLOCATED.try_with(|allocated| {
+                allocated.set(allocated.get() + layout.size());
+                let _ = PEAK.try_with(|peak| peak.set(peak.get().max(allocated.get())));
+            });
+        }
+
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+
+        // memory can be freed by a different thread than allocated it
+        let _ = ALLOCATED
+            .try_with(|allocated| allocated.set(allocated.get().saturating_sub(layout.size())));
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Decodes GIFs repeatedly and reports how long each phase of decoding took.")]
+struct Args {
+    /// GIFs to decode, or directories of GIFs.
+    #[arg(required = true)]
+    gif_paths: Vec<PathBuf>,
+
+    /// Number of times every GIF is decoded.
+    #[arg(long, default_value_t = 100)]
+    iterations: u32,
+}
+
+fn main() -> Result<()> {
+    let Args {
+        gif_paths,
+        iterations,
+    } = Args::parse();
+
+    run(Playlist::new(gif_paths)?.paths(), iterations)
+}
+
+/// Timings of a phase of decoding, over every iteration.
+#[derive(Debug, Default)]
+pub struct Timings {
+    total: Duration,
+    min: Option<Duration>,
+    iterations: u32,
+}
+
+impl Timings {
+    fn record(&mut self, elapsed: Duration) {
+        self.total += elapsed;
+        self.min = Some(self.min.map_or(elapsed, |min| min.min(elapsed)));
+        self.iterations += 1;
+    }
+
+    pub fn mean(&self) -> Duration {
+        self.total.checked_div(self.iterations).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "mean {:>10.3} ms, min {:>10.3} ms",
+            self.mean().as_secs_f64() * 1000.0,
+            self.min.unwrap_or_default().as_secs_f64() * 1000.0
+        )
+    }
+}
+
+/// The results of decoding a GIF repeatedly.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub frames: usize,
+    pub parse: Timings,
+    pub lzw: Timings,
+    pub composite: Timings,
+
+    /// The most memory, in bytes, allocated at once while decoding, not
+    /// counting the copy of the file that is parsed.
+    pub peak_memory: usize,
+}
+
+impl Report {
+    /// Frames decompressed and composited per second.
+    pub fn frames_per_second(&self) -> f64 {
+        let decoding = self.lzw.total + self.composite.total;
+        let frames = self.frames as f64 * self.lzw.iterations as f64;
+
+        frames / decoding.as_secs_f64()
+    }
+}
+
+/// Parses, decompresses and composites `data` `iterations` times, timing each
+/// phase separately. Frames are decoded one at a time, as the renderer decodes
+/// them, so only a single index stream is held in memory at once.
+pub fn bench(data: &[u8], iterations: u32) -> Result<Report> {
+    let mut report = Report::default();
+
+    for _ in 0..iterations {
+        let data = data.to_vec();
+
+        // measured from after the copy, so the file itself isn't counted
+        let baseline = ALLOCATED.with(Cell::get);
+        PEAK.with(|peak| peak.set(baseline));
+
+        let start = Instant::now();
+        let gif = Decoder::new(data).parse()?;
+        report.parse.record(start.elapsed());
+
+        let mut frame_decoder = gif.frame_decoder()?;
+
+        let frame_count = frame_decoder.frames().len();
+        let mut lzw = Duration::ZERO;
+        let mut composite = Duration::ZERO;
+
+        for n in 0..frame_count {
+            let start = Instant::now();
+            let index_streams = frame_decoder.decompress_index_streams(n..n + 1)?;
+            lzw += start.elapsed();
+
+            let start = Instant::now();
+            frame_decoder.next_frame_from(&index_streams[0])?;
+            composite += start.elapsed();
+        }
+
+        report.lzw.record(lzw);
+        report.composite.record(composite);
+        report.frames = frame_count;
+
+        let peak_memory = PEAK.with(Cell::get).saturating_sub(baseline);
+        report.peak_memory = report.peak_memory.max(peak_memory);
+    }
+
+    Ok(report)
+}
+
+/// Benchmarks every GIF in `paths` and prints a report for each.
+fn run(paths: &[impl AsRef<Path>], iterations: u32) -> Result<()> {
+    if iterations == 0 {
+        return Err(eyre!("Can not benchmark 0 iterations."));
+    }
+
+    for path in paths {
+        let path = path.as_ref();
+        let data = path
+            .to_str()
+            .ok_or_else(|| eyre!("Path is not valid UTF-8: {}", path.display()))
+            .and_then(|path| Ok(dump_gif(path)?));
+
+        match data.and_then(|data| bench(&data, iterations)) {
+            Ok(report) => print_report(path, iterations, &report),
+            Err(err) => eprintln!("{}: {}", path.display(), err),
+        }
+    }
+
+    Ok(())
+}
+
+fn print_report(path: &Path, iterations: u32, report: &Report) {
+    println!(
+        "{}: {} frames, {} iterations",
+        path.display(),
+        report.frames,
+        iterations
+    );
+    println!("  parse        {}", report.parse);
+    println!("  lzw          {}", report.lzw);
+    println!("  composite    {}", report.composite);
+    println!("  frames/s     {:.1}", report.frames_per_second());
+    println!(
+        "  peak memory  {:.1} KiB",
+        report.peak_memory as f64 / 1024.0
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report() -> Result<()> {
+        let data = dump_gif("../sample_gifs/sample_2_animation.gif")?;
+        let report = bench(&data, 3)?;
+
+        assert_eq!(report.frames, 3);
+        assert_eq!(report.parse.iterations, 3);
+        assert!(report.composite.min.is_some());
+        assert!(report.frames_per_second() > 0.0);
+
+        // at least the composited frames are allocated
+        let canvas = 11 * 29 * size_of::<u32>();
+        assert!(report.peak_memory >= canvas);
+
+        Ok(())
+    }
+}
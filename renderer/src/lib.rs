@@ -1 +1,3 @@
+//! Modules shared by the `jif-render` and `jif-render-bench` binaries.
 
+pub mod playlist;
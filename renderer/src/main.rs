@@ -1,24 +1,237 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use clap::Parser;
 use eyre::{eyre, Result};
-use minifb::{Window, WindowOptions};
+use minifb::{Key, KeyRepeat, Window, WindowOptions};
 
-use jif::{Decoder, dump_gif};
+use jif::gif_data_stream::GifDataStream;
 use jif::grammar::{Frame, LogicalScreenDescriptor};
+use jif::{dump_gif, Decoder, DelayPolicy};
+
+use jif_render::playlist::{modified, Playlist};
+
+use crate::player::{Control, Player};
+use crate::scheduler::Scheduler;
+use crate::terminal::Protocol;
+use crate::view::{parse_background, parse_scale, Background, Scale, View};
+
+mod player;
+mod scheduler;
+mod terminal;
+mod view;
+
+/// The longest the window goes without handling its events.
+const POLL_INTERVAL: Duration = Duration::from_millis(16);
+
+/// How often watched files are checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Size of the window when none of the GIFs can be opened.
+const DEFAULT_WINDOW_SIZE: (usize, usize) = (400, 300);
+
+/// What the window shows when a GIF fails to decode.
+const ERROR_COLOR: u32 = 0x40_0000;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    gif_path: PathBuf,
+    /// GIFs to play, or directories of GIFs.
+    #[arg(required = true)]
+    gif_paths: Vec<PathBuf>,
+
+    /// Reloads the GIF that is playing whenever it changes on disk.
+    #[arg(long)]
+    watch: bool,
+
+    /// How frame delays are interpreted: `raw`, `browser`, or a minimum delay
+    /// in milliseconds.
+    #[arg(long, default_value = "browser")]
+    delay_policy: DelayPolicy,
+
+    /// How large canvas pixels are drawn: an integer factor, or `fit` to
+    /// scale the canvas with the window.
+    #[arg(long, default_value = "1", value_parser = parse_scale)]
+    scale: Scale,
+
+    /// What transparent areas show: `checkerboard` or a color as `RRGGBB`.
+    /// Defaults to the background color of the GIF.
+    #[arg(long, value_parser = parse_background)]
+    background: Option<Background>,
+
+    /// Plays the GIF in the terminal instead of a window, drawn with
+    /// `half-block` characters (the default), `sixel` or `kitty` graphics.
+    #[arg(
+        long,
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "half-block",
+        value_name = "PROTOCOL"
+    )]
+    terminal: Option<Protocol>,
 }
 
 fn main() -> Result<()> {
-    let Args { gif_path } = Args::parse();
+    let Args {
+        gif_paths,
+        watch,
+        delay_policy,
+        scale,
+        background,
+        terminal,
+    } = Args::parse();
+
+    let mut playlist = Playlist::new(gif_paths)?;
+
+    if let Some(protocol) = terminal {
+        let options = terminal::Options {
+            protocol,
+            delay_policy,
+            scale,
+        };
+
+        for path in playlist.paths() {
+            let played = open(path).and_then(|gif| {
+                let view = View::new(&gif.logical_screen_descriptor, background);
+                terminal::play(&gif, &view, options)
+            });
+
+            if let Err(err) = played {
+                eprintln!("{}: {}", path.display(), err);
+            }
+        }
+
+        return Ok(());
+    }
+
+    // the window is sized for the first GIF that opens
+    let (window_width, window_height) = playlist
+        .paths()
+        .iter()
+        .find_map(|path| open(path).ok())
+        .map(|gif| View::new(&gif.logical_screen_descriptor, background).window_size(scale))
+        .unwrap_or(DEFAULT_WINDOW_SIZE);
+
+    if window_width > 1 << 14 || window_height > 1 << 14 {
+        return Err(eyre!(
+            "Window width or height can not be greater than 16384. Got width: {}, height: {}",
+            window_width,
+            window_height
+        ));
+    }
+
+    let mut window = Window::new(
+        "GIF renderer",
+        window_width,
+        window_height,
+        WindowOptions {
+            // other GIFs in the playlist are scaled to fit the window
+            resize: scale == Scale::Fit || playlist.len() > 1,
+            ..WindowOptions::default()
+        },
+    )?;
+
+    // frames are paced by the scheduler instead
+    window.set_target_fps(0);
+
+    let mut animation = Animation::load(playlist.current(), background, delay_policy);
+    let mut modified_at = modified(playlist.current());
+    let mut checked_at = Instant::now();
+    let mut title = String::new();
+    let mut redraw = true;
+    let mut size = window.get_size();
+    let mut buffer = vec![];
+
+    while window.is_open() && !window.is_key_down(Key::Escape) {
+        let keys = window.get_keys_pressed(KeyRepeat::Yes);
+        let mut reload = false;
+
+        for key in &keys {
+            match key {
+                Key::N | Key::PageDown => playlist.next(),
+                Key::P | Key::PageUp => playlist.previous(),
+                _ => continue,
+            }
+
+            reload = true;
+        }
+
+        if watch && checked_at.elapsed() >= WATCH_INTERVAL {
+            checked_at = Instant::now();
+            reload |= modified(playlist.current()) != modified_at;
+        }
+
+        if reload {
+            animation = Animation::load(playlist.current(), background, delay_policy);
+            modified_at = modified(playlist.current());
+            redraw = true;
+        }
+
+        let status = match &mut animation {
+            Ok(animation) => {
+                redraw |= animation.update(keys.into_iter().filter_map(control));
+                animation.status()
+            }
+            Err(err) => format!("error: {}", err),
+        };
+
+        let name = playlist.current().file_name().unwrap_or_default();
+        let next_title = match playlist.len() {
+            1 => format!("GIF renderer - {} - {}", name.display(), status),
+            len => format!(
+                "GIF renderer - {} ({}/{}) - {}",
+                name.display(),
+                playlist.position() + 1,
+                len,
+                status
+            ),
+        };
+        if next_title != title {
+            window.set_title(&next_title);
+            title = next_title;
+        }
+
+        redraw |= window.get_size() != size;
+        size = window.get_size();
 
-    let data = dump_gif(gif_path.to_str().expect("Failed to find path"))?;
+        // nothing can be drawn while the window is minimized
+        let (width, height) = size;
+        if width == 0 || height == 0 {
+            redraw = false;
+        }
+
+        if redraw {
+            match &animation {
+                Ok(animation) => animation.render(width, height, &mut buffer),
+                Err(_) => {
+                    buffer.clear();
+                    buffer.resize(width * height, ERROR_COLOR);
+                }
+            }
+
+            window.update_with_buffer(&buffer, width, height)?;
+            redraw = false;
+        } else {
+            window.update();
+        }
+
+        let wait = match &animation {
+            Ok(animation) => animation.wait(),
+            Err(_) => POLL_INTERVAL,
+        };
+        sleep(wait.min(POLL_INTERVAL));
+    }
+
+    Ok(())
+}
+
+fn open(path: &Path) -> Result<GifDataStream> {
+    let path = path
+        .to_str()
+        .ok_or_else(|| eyre!("Path is not valid UTF-8: {}", path.display()))?;
+
+    let data = dump_gif(path)?;
     let mut decoder = Decoder::new(data);
     let compressed_gif = decoder.parse()?;
 
@@ -44,26 +257,115 @@ fn main() -> Result<()> {
         ));
     }
 
-    let mut window = Window::new(
-        "GIF renderer",
-        canvas_width as usize,
-        canvas_height as usize,
-        WindowOptions::default(),
-    )?;
+    Ok(compressed_gif)
+}
 
-    let frames = compressed_gif.decompress()?;
+/// A decoded GIF and the state of its playback in the window.
+struct Animation {
+    view: View,
+    frames: Vec<Frame>,
+    delay_policy: DelayPolicy,
+    player: Player,
+    scheduler: Scheduler,
+}
 
-    while window.is_open() {
-        for frame in &frames {
-            let Frame { pixels, delay_time } = frame;
+impl Animation {
+    fn load(
+        path: &Path,
+        background: Option<Background>,
+        delay_policy: DelayPolicy,
+    ) -> Result<Self> {
+        let gif = open(path)?;
 
-            window.update_with_buffer(pixels, canvas_width as usize, canvas_height as usize)?;
+        let view = View::new(&gif.logical_screen_descriptor, background);
+        let mut frame_decoder = gif.frame_decoder()?;
 
-            if let Some(delay_time) = delay_time {
-                sleep(Duration::from_millis((delay_time * 10) as u64));
-            }
+        if view.transparent_background() {
+            frame_decoder = frame_decoder.with_transparent_background();
+        }
+
+        let mut frames = vec![];
+
+        while let Some(frame) = frame_decoder.next_frame()? {
+            frames.push(frame);
+        }
+
+        if frames.is_empty() {
+            return Err(eyre!("GIF has no frames."));
         }
+
+        let player = Player::new(frames.len());
+        let scheduler = Scheduler::new(Instant::now(), frames[0].delay(delay_policy));
+
+        Ok(Self {
+            view,
+            frames,
+            delay_policy,
+            player,
+            scheduler,
+        })
     }
 
-    Ok(())
+    /// Applies the controls and advances playback. Returns true if a
+    /// different frame should be shown.
+    fn update(&mut self, controls: impl Iterator<Item = Control>) -> bool {
+        let Self {
+            frames,
+            delay_policy,
+            player,
+            scheduler,
+            ..
+        } = self;
+        let delay = |index: usize| frames[index].delay(*delay_policy);
+
+        let mut redraw = false;
+        let mut controlled = false;
+
+        for control in controls {
+            redraw |= player.handle(control);
+            controlled = true;
+        }
+
+        if controlled {
+            scheduler.restart(Instant::now(), player.delay(delay(player.frame())));
+        }
+
+        if !player.paused() {
+            redraw |= scheduler.catch_up(Instant::now(), player, delay) > 0;
+        }
+
+        redraw
+    }
+
+    fn status(&self) -> String {
+        let frame = &self.frames[self.player.frame()];
+        self.player.status(frame.delay(self.delay_policy))
+    }
+
+    fn render(&self, width: usize, height: usize, buffer: &mut Vec<u32>) {
+        let pixels = &self.frames[self.player.frame()].pixels;
+        self.view.render(pixels, width, height, buffer);
+    }
+
+    /// How long until the next frame is due.
+    fn wait(&self) -> Duration {
+        match self.player.paused() {
+            true => POLL_INTERVAL,
+            false => self.scheduler.wait(Instant::now()),
+        }
+    }
+}
+
+/// Space pauses, the arrow keys step between frames, +/- change the speed and
+/// L toggles looping.
+const fn control(key: Key) -> Option<Control> {
+    match key {
+        Key::Space => Some(Control::TogglePause),
+        Key::Right => Some(Control::StepForward),
+        Key::Left => Some(Control::StepBackward),
+        Key::Equal | Key::NumPadPlus => Some(Control::Faster),
+        Key::Minus | Key::NumPadMinus => Some(Control::Slower),
+        Key::L => Some(Control::ToggleLoop),
+        _ => None,
+    }
 }
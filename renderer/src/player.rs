@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+/// Playback speeds, as multiples of the speed the GIF was authored at.
+const SPEEDS: [f32; 7] = [0.25, 0.5, 0.75, 1.0, 1.5, 2.0, 4.0];
+const DEFAULT_SPEED: usize = 3;
+
+/// An input that changes playback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    TogglePause,
+    StepForward,
+    StepBackward,
+    Faster,
+    Slower,
+    ToggleLoop,
+}
+
+/// Tracks which frame is shown and how playback advances through the frames.
+#[derive(Debug)]
+pub struct Player {
+    frame_count: usize,
+    frame: usize,
+    paused: bool,
+    looping: bool,
+    speed: usize,
+}
+
+impl Player {
+    pub fn new(frame_count: usize) -> Self {
+        Self {
+            frame_count,
+            frame: 0,
+            paused: false,
+            looping: true,
+            speed: DEFAULT_SPEED,
+        }
+    }
+
+    /// Index of the frame that is shown.
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn speed(&self) -> f32 {
+        SPEEDS[self.speed]
+    }
+
+    /// Applies a control. Returns true if a different frame should be shown.
+    pub fn handle(&mut self, control: Control) -> bool {
+        match control {
+            Control::TogglePause => {
+                self.paused = !self.paused;
+
+                // resuming a finished animation starts it over
+                if !self.paused && self.at_end() && !self.looping {
+                    self.frame = 0;
+                    return true;
+                }
+            }
+            Control::StepForward => {
+                self.paused = true;
+                self.frame = (self.frame + 1) % self.frame_count;
+                return true;
+            }
+            Control::StepBackward => {
+                self.paused = true;
+                self.frame = (self.frame + self.frame_count - 1) % self.frame_count;
+                return true;
+            }
+            Control::Faster => self.speed = (self.speed + 1).min(SPEEDS.len() - 1),
+            Control::Slower => self.speed = self.speed.saturating_sub(1),
+            Control::ToggleLoop => self.looping = !self.looping,
+        }
+
+        false
+    }
+
+    /// Moves on to the next frame once the shown frame's delay has passed.
+    /// Without looping, playback pauses on the last frame.
+    pub fn advance(&mut self) {
+        if self.paused {
+            return;
+        }
+
+        if self.at_end() && !self.looping {
+            self.paused = true;
+            return;
+        }
+
+        self.frame = (self.frame + 1) % self.frame_count;
+    }
+
+    /// How long a frame with the given delay is shown for at the current
+    /// speed.
+    pub fn delay(&self, delay: Duration) -> Duration {
+        delay.div_f64(self.speed() as f64)
+    }
+
+    /// A readout of the playback state, for the window title.
+    pub fn status(&self, delay: Duration) -> String {
+        format!(
+            "frame {}/{} - {} ms - {}x{}{}",
+            self.frame + 1,
+            self.frame_count,
+            delay.as_millis(),
+            self.speed(),
+            if self.paused { " - paused" } else { "" },
+            if self.looping { "" } else { " - no loop" },
+        )
+    }
+
+    fn at_end(&self) -> bool {
+        self.frame + 1 == self.frame_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn step_and_loop() {
+        let mut player = Player::new(3);
+
+        player.advance();
+        player.advance();
+        player.advance();
+        assert_eq!(player.frame(), 0);
+
+        assert!(player.handle(Control::StepBackward));
+        assert!(player.paused());
+        assert_eq!(player.frame(), 2);
+
+        player.advance();
+        assert_eq!(player.frame(), 2);
+
+        player.handle(Control::ToggleLoop);
+        player.handle(Control::TogglePause);
+        assert_eq!(player.frame(), 0);
+
+        player.advance();
+        player.advance();
+        player.advance();
+        assert_eq!(player.frame(), 2);
+        assert!(player.paused());
+    }
+
+    #[test]
+    fn speed() {
+        let mut player = Player::new(1);
+        let delay = Duration::from_millis(100);
+
+        player.handle(Control::Faster);
+        player.handle(Control::Faster);
+        assert_eq!(player.delay(delay), Duration::from_millis(50));
+
+        for _ in 0..SPEEDS.len() {
+            player.handle(Control::Slower);
+        }
+        assert_eq!(player.delay(delay), Duration::from_millis(400));
+    }
+}
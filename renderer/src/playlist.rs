@@ -0,0 +1,116 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use eyre::{eyre, Result};
+
+/// The GIFs to play, and which one is playing.
+#[derive(Debug)]
+pub struct Playlist {
+    paths: Vec<PathBuf>,
+    current: usize,
+}
+
+impl Playlist {
+    /// Creates a playlist of `paths`, where directories are replaced by the
+    /// GIFs directly inside them, sorted by name.
+    pub fn new(paths: Vec<PathBuf>) -> Result<Self> {
+        let mut expanded = vec![];
+
+        for path in paths {
+            if !path.is_dir() {
+                expanded.push(path);
+                continue;
+            }
+
+            let mut gifs = fs::read_dir(&path)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<io::Result<Vec<_>>>()?;
+            gifs.retain(|path| path.is_file() && is_gif(path));
+            gifs.sort();
+
+            expanded.extend(gifs);
+        }
+
+        if expanded.is_empty() {
+            return Err(eyre!("No GIFs to play."));
+        }
+
+        Ok(Self {
+            paths: expanded,
+            current: 0,
+        })
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    pub fn current(&self) -> &Path {
+        &self.paths[self.current]
+    }
+
+    /// Index of the GIF that is playing.
+    pub fn position(&self) -> usize {
+        self.current
+    }
+
+    // a playlist is never empty, as `new` rejects one without GIFs
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Moves to the next GIF, wrapping around to the first.
+    pub fn next(&mut self) {
+        self.current = (self.current + 1) % self.paths.len();
+    }
+
+    /// Moves to the previous GIF, wrapping around to the last.
+    pub fn previous(&mut self) {
+        self.current = (self.current + self.paths.len() - 1) % self.paths.len();
+    }
+}
+
+/// When the file at `path` was last modified, if it can be told.
+pub fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn is_gif(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("gif"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directory() -> Result<()> {
+        let single = PathBuf::from("../sample_gifs/sample_1.gif");
+        let mut playlist = Playlist::new(vec![single.clone(), PathBuf::from("../sample_gifs")])?;
+
+        assert!(playlist.len() > 2);
+        assert_eq!(playlist.current(), single);
+        assert!(playlist.paths()[1..]
+            .windows(2)
+            .all(|paths| paths[0] < paths[1]));
+        assert!(playlist.paths().iter().all(|path| is_gif(path)));
+
+        playlist.previous();
+        assert_eq!(playlist.position(), playlist.len() - 1);
+        playlist.next();
+        assert_eq!(playlist.position(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn empty() {
+        assert!(Playlist::new(vec![]).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+use std::time::{Duration, Instant};
+
+use crate::player::Player;
+
+/// The shortest time a frame is scheduled for. Frames without a delay would
+/// otherwise be due again immediately, and a GIF made only of them would spin
+/// the CPU.
+const MIN_DELAY: Duration = Duration::from_millis(10);
+
+/// How far playback may fall behind before it gives up on catching up and
+/// restarts from the current frame, e.g. after the window was dragged.
+const MAX_LAG: Duration = Duration::from_secs(1);
+
+/// Decides when the next frame is due.
+///
+/// Each deadline is computed from the previous one rather than from when a
+/// frame was actually shown, so the time spent rendering does not add up to
+/// drift over the course of an animation.
+#[derive(Debug)]
+pub struct Scheduler {
+    deadline: Instant,
+}
+
+impl Scheduler {
+    /// Schedules the frame shown at `now` to be replaced after `delay`.
+    pub fn new(now: Instant, delay: Duration) -> Self {
+        Self {
+            deadline: now + delay.max(MIN_DELAY),
+        }
+    }
+
+    /// Starts timing the shown frame over, e.g. after the user stepped to it.
+    pub fn restart(&mut self, now: Instant, delay: Duration) {
+        *self = Self::new(now, delay);
+    }
+
+    /// Advances `player` past every frame whose deadline has passed, where
+    /// `delay` gives how long the frame at an index is shown for. Frames
+    /// that are already over by the time they would be shown are dropped.
+    ///
+    /// Returns the number of frames advanced by.
+    pub fn catch_up(
+        &mut self,
+        now: Instant,
+        player: &mut Player,
+        delay: impl Fn(usize) -> Duration,
+    ) -> usize {
+        if now.saturating_duration_since(self.deadline) > MAX_LAG {
+            player.advance();
+            self.restart(now, player.delay(delay(player.frame())));
+            return 1;
+        }
+
+        let mut advanced = 0;
+
+        while !player.paused() && self.deadline <= now {
+            player.advance();
+
+            // without looping, playback stops on the last frame
+            if player.paused() {
+                break;
+            }
+
+            self.deadline += player.delay(delay(player.frame())).max(MIN_DELAY);
+            advanced += 1;
+        }
+
+        advanced
+    }
+
+    /// How long until the next frame is due.
+    pub fn wait(&self, now: Instant) -> Duration {
+        self.deadline.saturating_duration_since(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELAY: Duration = Duration::from_millis(100);
+
+    #[test]
+    fn compensates_for_render_time() {
+        let start = Instant::now();
+        let mut player = Player::new(4);
+        let mut scheduler = Scheduler::new(start, DELAY);
+
+        assert_eq!(scheduler.catch_up(start, &mut player, |_| DELAY), 0);
+        assert_eq!(scheduler.wait(start), DELAY);
+
+        // the frame was shown late, so the next one gets less time
+        let late = start + DELAY + Duration::from_millis(30);
+        assert_eq!(scheduler.catch_up(late, &mut player, |_| DELAY), 1);
+        assert_eq!(scheduler.wait(late), Duration::from_millis(70));
+    }
+
+    #[test]
+    fn drops_frames() {
+        let start = Instant::now();
+        let mut player = Player::new(4);
+        let mut scheduler = Scheduler::new(start, DELAY);
+
+        let now = start + 3 * DELAY + Duration::from_millis(10);
+        assert_eq!(scheduler.catch_up(now, &mut player, |_| DELAY), 3);
+        assert_eq!(player.frame(), 3);
+        assert_eq!(scheduler.wait(now), Duration::from_millis(90));
+
+        // after a long stall, playback continues from the next frame
+        let now = now + 20 * DELAY;
+        assert_eq!(scheduler.catch_up(now, &mut player, |_| DELAY), 1);
+        assert_eq!(player.frame(), 0);
+        assert_eq!(scheduler.wait(now), DELAY);
+    }
+
+    #[test]
+    fn zero_delays() {
+        let start = Instant::now();
+        let mut player = Player::new(2);
+        let mut scheduler = Scheduler::new(start, Duration::ZERO);
+
+        assert_eq!(scheduler.wait(start), MIN_DELAY);
+
+        let now = start + 5 * MIN_DELAY;
+        assert_eq!(scheduler.catch_up(now, &mut player, |_| Duration::ZERO), 5);
+    }
+}
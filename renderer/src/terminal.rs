@@ -0,0 +1,394 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::thread::sleep;
+use std::time::Instant;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use clap::ValueEnum;
+use eyre::{eyre, Result};
+
+use jif::gif_data_stream::GifDataStream;
+use jif::DelayPolicy;
+
+use crate::view::{Scale, View};
+
+/// Width, in columns, of the terminal when `COLUMNS` is not set.
+const DEFAULT_COLUMNS: usize = 80;
+
+/// Most bytes of base64 a single Kitty graphics escape may carry.
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// Most colors a sixel image is drawn with.
+const SIXEL_COLORS: usize = 256;
+
+/// How frames are drawn in the terminal.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Protocol {
+    /// ANSI truecolor `▀` characters, two pixels to a cell. Works in most
+    /// terminals, at a low resolution.
+    HalfBlock,
+
+    /// DEC sixel graphics, limited to 256 colors a frame.
+    Sixel,
+
+    /// The Kitty terminal graphics protocol.
+    Kitty,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    pub protocol: Protocol,
+    pub delay_policy: DelayPolicy,
+    pub scale: Scale,
+}
+
+/// Plays `gif` on standard output, as many times as its loop count asks for.
+pub fn play(gif: &GifDataStream, view: &View, options: Options) -> Result<()> {
+    let Options {
+        protocol,
+        delay_policy,
+        scale,
+    } = options;
+
+    let (width, height) = match protocol {
+        // half blocks are square, as cells are about twice as high as wide
+        Protocol::HalfBlock => {
+            let (width, height) = view.window_size(scale);
+            view.fit(width.min(columns()), height)
+        }
+        Protocol::Sixel | Protocol::Kitty => view.window_size(scale),
+    };
+
+    if width > 1 << 14 || height > 1 << 14 {
+        return Err(eyre!(
+            "Image width or height can not be greater than 16384. Got width: {}, height: {}",
+            width,
+            height
+        ));
+    }
+
+    let mut out = io::stdout().lock();
+    let mut buffer = vec![];
+    let mut encoded = vec![];
+    let mut deadline = Instant::now();
+    let mut plays = 0;
+
+    // clear the screen, so every frame can be drawn from its top left corner
+    write!(out, "\x1b[2J")?;
+
+    loop {
+        let mut frame_decoder = gif.frame_decoder()?;
+
+        if view.transparent_background() {
+            frame_decoder = frame_decoder.with_transparent_background();
+        }
+
+        for frame in frame_decoder {
+            let frame = frame?;
+
+            view.render(&frame.pixels, width, height, &mut buffer);
+
+            encoded.clear();
+            write!(encoded, "\x1b[H")?;
+            write_frame(protocol, &buffer, width, height, &mut encoded)?;
+            out.write_all(&encoded)?;
+            out.flush()?;
+
+            deadline += frame.delay(delay_policy);
+            sleep(deadline.saturating_duration_since(Instant::now()));
+        }
+
+        plays += 1;
+
+        match gif.loop_count() {
+            Some(0) => continue,
+            Some(loop_count) if plays <= loop_count as usize => continue,
+            _ => break,
+        }
+    }
+
+    writeln!(out)?;
+
+    Ok(())
+}
+
+/// Encodes `pixels`, `width` by `height` `0x00RRGGBB` pixels, for the given
+/// protocol.
+pub fn write_frame(
+    protocol: Protocol,
+    pixels: &[u32],
+    width: usize,
+    height: usize,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    match protocol {
+        Protocol::HalfBlock => write_half_blocks(pixels, width, height, out),
+        Protocol::Sixel => write_sixel(pixels, width, height, out),
+        Protocol::Kitty => write_kitty(pixels, width, height, out),
+    }
+}
+
+fn write_half_blocks(
+    pixels: &[u32],
+    width: usize,
+    height: usize,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    for y in (0..height).step_by(2) {
+        let mut colors = None;
+
+        for x in 0..width {
+            let top = pixels[y * width + x];
+            let bottom = (y + 1 < height).then(|| pixels[(y + 1) * width + x]);
+
+            // colors are only written when they change from the previous cell
+            if colors != Some((top, bottom)) {
+                let [_, r, g, b] = top.to_be_bytes();
+                write!(out, "\x1b[38;2;{};{};{}m", r, g, b)?;
+
+                match bottom {
+                    Some(bottom) => {
+                        let [_, r, g, b] = bottom.to_be_bytes();
+                        write!(out, "\x1b[48;2;{};{};{}m", r, g, b)?;
+                    }
+                    None => write!(out, "\x1b[49m")?,
+                }
+
+                colors = Some((top, bottom));
+            }
+
+            write!(out, "▀")?;
+        }
+
+        writeln!(out, "\x1b[0m")?;
+    }
+
+    Ok(())
+}
+
+fn write_sixel(
+    pixels: &[u32],
+    width: usize,
+    height: usize,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let (palette, indices) = palette(pixels);
+
+    write!(out, "\x1bPq\"1;1;{};{}", width, height)?;
+
+    for (index, color) in palette.iter().enumerate() {
+        let [_, r, g, b] = color.to_be_bytes();
+        write!(
+            out,
+            "#{};2;{};{};{}",
+            index,
+            percent(r),
+            percent(g),
+            percent(b)
+        )?;
+    }
+
+    let mut sixels = vec![0_u8; width];
+
+    // every band covers six rows of pixels
+    for top in (0..height).step_by(6) {
+        let rows = top..(top + 6).min(height);
+
+        let mut colors = rows
+            .clone()
+            .flat_map(|y| &indices[y * width..(y + 1) * width])
+            .copied()
+            .collect::<Vec<_>>();
+        colors.sort_unstable();
+        colors.dedup();
+
+        for color in colors {
+            for (x, sixel) in sixels.iter_mut().enumerate() {
+                *sixel = rows
+                    .clone()
+                    .filter(|y| indices[y * width + x] == color)
+                    .fold(0, |bits, y| bits | 1 << (y - top));
+            }
+
+            write!(out, "#{}", color)?;
+
+            for run in sixels.chunk_by(|a, b| a == b) {
+                let c = char::from(b'?' + run[0]);
+
+                match run.len() {
+                    1..=3 => (0..run.len()).try_for_each(|_| write!(out, "{}", c))?,
+                    len => write!(out, "!{}{}", len, c)?,
+                }
+            }
+
+            // return to the start of the band for the next color
+            write!(out, "$")?;
+        }
+
+        write!(out, "-")?;
+    }
+
+    write!(out, "\x1b\\")
+}
+
+fn write_kitty(
+    pixels: &[u32],
+    width: usize,
+    height: usize,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let rgb = pixels
+        .iter()
+        .flat_map(|pixel| {
+            let [_, r, g, b] = pixel.to_be_bytes();
+            [r, g, b]
+        })
+        .collect::<Vec<_>>();
+    let data = STANDARD.encode(rgb);
+
+    let chunks = data.as_bytes().chunks(KITTY_CHUNK_SIZE);
+    let last = chunks.len() - 1;
+
+    for (index, chunk) in chunks.enumerate() {
+        let more = u8::from(index != last);
+
+        // every frame replaces image 1, and leaves the cursor where it is
+        match index {
+            0 => write!(
+                out,
+                "\x1b_Ga=T,f=24,s={},v={},i=1,C=1,q=2,m={};",
+                width, height, more
+            )?,
+            _ => write!(out, "\x1b_Gm={};", more)?,
+        }
+
+        out.write_all(chunk)?;
+        write!(out, "\x1b\\")?;
+    }
+
+    Ok(())
+}
+
+// the colors of `pixels`, and the index of every pixel's color. Frames with
+// too many colors are reduced to 3 bits of red and green and 2 of blue.
+fn palette(pixels: &[u32]) -> (Vec<u32>, Vec<usize>) {
+    let mut palette = vec![];
+    let mut lookup = HashMap::new();
+
+    for pixel in pixels {
+        if lookup.len() == SIXEL_COLORS && !lookup.contains_key(pixel) {
+            let palette = (0..SIXEL_COLORS as u32)
+                .map(|index| {
+                    let r = (index >> 5) * 0x24;
+                    let g = (index >> 2 & 0b111) * 0x24;
+                    let b = (index & 0b11) * 0x55;
+                    r << 16 | g << 8 | b
+                })
+                .collect();
+            let indices = pixels.iter().copied().map(quantize).collect();
+
+            return (palette, indices);
+        }
+
+        lookup.entry(*pixel).or_insert_with(|| {
+            palette.push(*pixel);
+            palette.len() - 1
+        });
+    }
+
+    let indices = pixels.iter().map(|pixel| lookup[pixel]).collect();
+
+    (palette, indices)
+}
+
+// the index of a pixel's color in the 3-3-2 bit palette
+const fn quantize(pixel: u32) -> usize {
+    let [_, r, g, b] = pixel.to_be_bytes();
+    (r as usize >> 5) << 5 | (g as usize >> 5) << 2 | b as usize >> 6
+}
+
+// sixel colors are given in percent
+const fn percent(channel: u8) -> u32 {
+    (channel as u32 * 100 + 127) / 255
+}
+
+fn columns() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .unwrap_or(DEFAULT_COLUMNS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(protocol: Protocol, pixels: &[u32], width: usize, height: usize) -> String {
+        let mut out = vec![];
+        write_frame(protocol, pixels, width, height, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn half_block() {
+        let out = encode(
+            Protocol::HalfBlock,
+            &[0xFF_0000, 0xFF_0000, 0x00_00FF, 0x00_00FF, 0x00_FF00, 0],
+            2,
+            3,
+        );
+
+        assert_eq!(
+            out,
+            "\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m▀▀\x1b[0m\n\
+             \x1b[38;2;0;255;0m\x1b[49m▀\x1b[38;2;0;0;0m\x1b[49m▀\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn sixel() {
+        // a red column next to a green and blue one, 7 pixels high
+        let pixels = [0xFF_0000, 0x00_FF00]
+            .repeat(6)
+            .into_iter()
+            .chain([0xFF_0000, 0x00_00FF])
+            .collect::<Vec<_>>();
+        let out = encode(Protocol::Sixel, &pixels, 2, 7);
+
+        assert_eq!(
+            out,
+            "\x1bPq\"1;1;2;7\
+             #0;2;100;0;0#1;2;0;100;0#2;2;0;0;100\
+             #0~?$#1?~$-\
+             #0@?$#2?@$-\
+             \x1b\\"
+        );
+    }
+
+    #[test]
+    fn sixel_run_length() {
+        let out = encode(Protocol::Sixel, &[0; 5], 5, 1);
+        assert!(out.contains("#0!5@$-"));
+    }
+
+    #[test]
+    fn kitty_chunks() {
+        // 4 KiB of base64 carries 3 KiB of pixels, or 1024 pixels
+        let out = encode(Protocol::Kitty, &[0; 1025], 1025, 1);
+
+        assert!(out.starts_with("\x1b_Ga=T,f=24,s=1025,v=1,i=1,C=1,q=2,m=1;AAAA"));
+        assert!(out.contains("\x1b\\\x1b_Gm=0;AAAA\x1b\\"));
+        assert_eq!(out.matches("\x1b_G").count(), 2);
+    }
+
+    #[test]
+    fn quantizes_large_palettes() {
+        let pixels = (0..SIXEL_COLORS as u32 + 1).collect::<Vec<_>>();
+        let (palette, indices) = palette(&pixels);
+
+        assert_eq!(palette.len(), SIXEL_COLORS);
+        assert_eq!(palette[SIXEL_COLORS - 1], 0xFC_FCFF);
+        assert!(indices.iter().all(|index| *index < SIXEL_COLORS));
+    }
+}
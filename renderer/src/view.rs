@@ -0,0 +1,259 @@
+use jif::grammar::{LogicalScreenDescriptor, TRANSPARENT};
+
+/// The area a GIF scaled to fit may take up when the window opens.
+const FIT_WIDTH: usize = 800;
+const FIT_HEIGHT: usize = 600;
+
+/// Size, in window pixels, of a checkerboard square.
+const CHECKER_SIZE: usize = 8;
+const CHECKER_LIGHT: u32 = 0xCC_CCCC;
+const CHECKER_DARK: u32 = 0x99_9999;
+
+/// How the canvas is sized in the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scale {
+    /// Every canvas pixel is drawn as a block of this many window pixels.
+    Factor(usize),
+
+    /// The canvas is scaled to fill the window, which can be resized.
+    Fit,
+}
+
+/// What is drawn behind transparent pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Background {
+    Checkerboard,
+
+    /// A `0x00RRGGBB` color.
+    Color(u32),
+}
+
+pub fn parse_scale(scale: &str) -> Result<Scale, String> {
+    match scale {
+        "fit" => Ok(Scale::Fit),
+        factor => match factor.parse() {
+            Ok(0) | Err(_) => Err(format!(
+                "expected `fit` or a positive integer, got `{}`",
+                factor
+            )),
+            Ok(factor) => Ok(Scale::Factor(factor)),
+        },
+    }
+}
+
+pub fn parse_background(background: &str) -> Result<Background, String> {
+    match background {
+        "checkerboard" => Ok(Background::Checkerboard),
+        color => jif::flags::parse_color(color)
+            .map(Background::Color)
+            .map_err(|_| {
+                format!(
+                    "expected `checkerboard` or a color as `RRGGBB`, got `{}`",
+                    background
+                )
+            }),
+    }
+}
+
+/// Draws composited frames into a window of any size, keeping the shape of
+/// the canvas and sampling the nearest canvas pixel.
+#[derive(Debug)]
+pub struct View {
+    canvas_width: usize,
+    canvas_height: usize,
+
+    /// Width of a canvas pixel divided by its height.
+    aspect_ratio: f32,
+
+    /// Drawn behind transparent pixels. Without one, frames are composited
+    /// onto the GIF's background color, and black shows where that is
+    /// missing or around the canvas.
+    background: Option<Background>,
+}
+
+impl View {
+    pub fn new(lsd: &LogicalScreenDescriptor, background: Option<Background>) -> Self {
+        // the descriptor stores the aspect ratio as `ratio * 64 - 15`, and 0
+        // when no ratio is given
+        let aspect_ratio = match lsd.pixel_aspect_ratio {
+            0 => 1.0,
+            ratio => (ratio as f32 + 15.0) / 64.0,
+        };
+
+        Self {
+            canvas_width: lsd.canvas_width as usize,
+            canvas_height: lsd.canvas_height as usize,
+            aspect_ratio,
+            background,
+        }
+    }
+
+    /// Whether frames should be composited onto a transparent canvas, so the
+    /// background shows through.
+    pub const fn transparent_background(&self) -> bool {
+        self.background.is_some()
+    }
+
+    /// Size of the window the canvas should open in.
+    pub fn window_size(&self, scale: Scale) -> (usize, usize) {
+        let factor = match scale {
+            Scale::Factor(factor) => factor,
+            Scale::Fit => {
+                let (width, height) = self.display_size(1);
+                (FIT_WIDTH / width).min(FIT_HEIGHT / height).max(1)
+            }
+        };
+
+        self.display_size(factor)
+    }
+
+    /// Draws `pixels`, a composited frame, into `out`, a window `width` by
+    /// `height` pixels in size. The canvas is centered, and any space around
+    /// it shows the background.
+    pub fn render(&self, pixels: &[u32], width: usize, height: usize, out: &mut Vec<u32>) {
+        out.clear();
+
+        let (image_width, image_height) = self.fit(width, height);
+        let left = (width - image_width) / 2;
+        let top = (height - image_height) / 2;
+
+        for y in 0..height {
+            for x in 0..width {
+                let inside = (left..left + image_width).contains(&x)
+                    && (top..top + image_height).contains(&y);
+
+                let pixel = match inside {
+                    true => {
+                        let source_x = (x - left) * self.canvas_width / image_width;
+                        let source_y = (y - top) * self.canvas_height / image_height;
+                        pixels[source_y * self.canvas_width + source_x]
+                    }
+                    false => TRANSPARENT,
+                };
+
+                out.push(match pixel {
+                    TRANSPARENT => self.background(x, y),
+                    pixel => pixel,
+                });
+            }
+        }
+    }
+
+    /// The largest size with the same shape as the canvas that fits in
+    /// `width` by `height` pixels.
+    pub fn fit(&self, width: usize, height: usize) -> (usize, usize) {
+        let (display_width, display_height) = self.display_size(1);
+
+        match width.saturating_mul(display_height) <= height.saturating_mul(display_width) {
+            true => (width, (width * display_height / display_width).max(1)),
+            false => ((height * display_width / display_height).max(1), height),
+        }
+    }
+
+    // size of the canvas with every pixel drawn `factor` window pixels high,
+    // saturating so that oversized windows can be reported
+    fn display_size(&self, factor: usize) -> (usize, usize) {
+        let width = (self.canvas_width as f32 * self.aspect_ratio * factor as f32).round();
+
+        (
+            (width as usize).max(1),
+            self.canvas_height.saturating_mul(factor),
+        )
+    }
+
+    fn background(&self, x: usize, y: usize) -> u32 {
+        match self.background.unwrap_or(Background::Color(0)) {
+            Background::Checkerboard => match (x / CHECKER_SIZE + y / CHECKER_SIZE) % 2 {
+                0 => CHECKER_LIGHT,
+                _ => CHECKER_DARK,
+            },
+            Background::Color(color) => color,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lsd(
+        canvas_width: u16,
+        canvas_height: u16,
+        pixel_aspect_ratio: u8,
+    ) -> LogicalScreenDescriptor {
+        LogicalScreenDescriptor {
+            canvas_width,
+            canvas_height,
+            packed_field: 0,
+            background_color_index: 0,
+            pixel_aspect_ratio,
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_scale("fit"), Ok(Scale::Fit));
+        assert_eq!(parse_scale("4"), Ok(Scale::Factor(4)));
+        assert!(parse_scale("0").is_err());
+
+        assert_eq!(
+            parse_background("checkerboard"),
+            Ok(Background::Checkerboard)
+        );
+        assert_eq!(
+            parse_background("#FF8000"),
+            Ok(Background::Color(0xFF_8000))
+        );
+        assert!(parse_background("FFF").is_err());
+    }
+
+    #[test]
+    fn window_size() {
+        let view = View::new(&lsd(10, 10, 0), Some(Background::Color(0)));
+        assert_eq!(view.window_size(Scale::Factor(3)), (30, 30));
+        assert_eq!(view.window_size(Scale::Fit), (600, 600));
+
+        // pixels twice as wide as they are high
+        let view = View::new(&lsd(10, 10, 113), Some(Background::Color(0)));
+        assert_eq!(view.window_size(Scale::Factor(3)), (60, 30));
+
+        let huge = Scale::Factor(usize::MAX);
+        assert_eq!(view.window_size(huge), (usize::MAX, usize::MAX));
+    }
+
+    #[test]
+    fn render() {
+        let view = View::new(&lsd(2, 1, 0), Some(Background::Color(0xFF_FFFF)));
+        let mut out = vec![];
+
+        view.render(&[1, TRANSPARENT], 4, 2, &mut out);
+        assert_eq!(
+            out,
+            [1, 1, 0xFF_FFFF, 0xFF_FFFF, 1, 1, 0xFF_FFFF, 0xFF_FFFF]
+        );
+
+        // a taller window centers the canvas vertically
+        view.render(&[1, 2], 2, 3, &mut out);
+        assert_eq!(out, [0xFF_FFFF, 0xFF_FFFF, 1, 2, 0xFF_FFFF, 0xFF_FFFF]);
+    }
+
+    #[test]
+    fn checkerboard() {
+        let view = View::new(&lsd(1, 1, 0), Some(Background::Checkerboard));
+        let mut out = vec![];
+
+        view.render(&[TRANSPARENT], 2 * CHECKER_SIZE, 1, &mut out);
+        assert_eq!(out[0], CHECKER_LIGHT);
+        assert_eq!(out[CHECKER_SIZE], CHECKER_DARK);
+    }
+
+    #[test]
+    fn gif_background() {
+        let view = View::new(&lsd(1, 1, 0), None);
+        let mut out = vec![];
+
+        view.render(&[TRANSPARENT], 1, 2, &mut out);
+        assert!(!view.transparent_background());
+        assert_eq!(out, [0, 0]);
+    }
+}
@@ -0,0 +1,201 @@
+use std::rc::Rc;
+
+use jif::gif_data_stream::GifDataStream;
+use jif::{Decoder, DelayPolicy, FrameDecoder, FrameInfo, PixelFormat};
+use js_sys::Uint8ClampedArray;
+use wasm_bindgen::prelude::*;
+
+/// The largest canvas, in pixels, a [`Gif`] decodes unless told otherwise:
+/// 8192x4096, or 128 MiB of RGBA pixels.
+pub const DEFAULT_MAX_CANVAS_AREA: usize = 8192 * 4096;
+
+/// A parsed GIF data stream.
+#[wasm_bindgen]
+pub struct Gif {
+    gif: Rc<GifDataStream>,
+    frames: Vec<FrameInfo>,
+}
+
+#[wasm_bindgen]
+impl Gif {
+    /// Parses a GIF from its bytes. Fails if the canvas or any frame has more
+    /// than `maxCanvasArea` pixels, which defaults to
+    /// [`DEFAULT_MAX_CANVAS_AREA`], so that untrusted GIFs can't exhaust
+    /// memory.
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: Vec<u8>, max_canvas_area: Option<usize>) -> Result<Gif, JsError> {
+        let gif = Decoder::new(data)
+            .with_max_canvas_area(max_canvas_area.unwrap_or(DEFAULT_MAX_CANVAS_AREA))
+            .parse()
+            .map_err(to_js_error)?;
+        let frames = gif.frame_decoder().map_err(to_js_error)?.frames().to_vec();
+
+        Ok(Self {
+            gif: Rc::new(gif),
+            frames,
+        })
+    }
+
+    /// Width of the canvas, in pixels.
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u16 {
+        self.gif.logical_screen_descriptor.canvas_width
+    }
+
+    /// Height of the canvas, in pixels.
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u16 {
+        self.gif.logical_screen_descriptor.canvas_height
+    }
+
+    /// The version of the data stream, usually "87a" or "89a".
+    #[wasm_bindgen(getter)]
+    pub fn version(&self) -> String {
+        self.gif.version.to_string()
+    }
+
+    #[wasm_bindgen(getter, js_name = frameCount)]
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Describes every frame without decoding any image data.
+    #[wasm_bindgen(js_name = frameMetadata)]
+    pub fn frame_metadata(&self) -> Result<Vec<FrameMetadata>, JsError> {
+        self.frames
+            .iter()
+            .enumerate()
+            .map(|(index, frame)| FrameMetadata::new(index, frame, &self.gif))
+            .collect::<jif::Result<_>>()
+            .map_err(to_js_error)
+    }
+
+    /// Returns an iterator that composites the frames one at a time.
+    pub fn frames(&self) -> Result<FrameIterator, JsError> {
+        let decoder = FrameDecoder::new(Rc::clone(&self.gif))
+            .map_err(to_js_error)?
+            .with_transparent_background()
+            .with_pixel_format(PixelFormat::Rgba8);
+
+        Ok(FrameIterator {
+            decoder,
+            buffer: vec![],
+        })
+    }
+}
+
+/// Where a frame is drawn on the canvas, and for how long it is displayed.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub struct FrameMetadata {
+    pub index: usize,
+    pub left: u16,
+    pub top: u16,
+    pub width: u16,
+    pub height: u16,
+
+    /// How long the frame is displayed for, in milliseconds, matching how
+    /// browsers play GIFs back.
+    pub delay: u32,
+
+    /// Delay time of the frame in hundredths of a second, as it appears in
+    /// the data stream, or `undefined` if the frame has none.
+    #[wasm_bindgen(js_name = delayTime)]
+    pub delay_time: Option<u16>,
+
+    /// Whether the frame can be composited without any of the frames before it.
+    pub keyframe: bool,
+}
+
+impl FrameMetadata {
+    fn new(index: usize, frame: &FrameInfo, gif: &GifDataStream) -> jif::Result<Self> {
+        let image_descriptor = &frame.table_based_image(gif)?.image_descriptor;
+        let delay_time = frame
+            .graphic_control_extension(gif)?
+            .map(|gce| gce.delay_time);
+
+        Ok(Self {
+            index,
+            left: image_descriptor.image_left,
+            top: image_descriptor.image_top,
+            width: image_descriptor.image_width,
+            height: image_descriptor.image_height,
+            delay: delay_millis(delay_time),
+            delay_time,
+            keyframe: frame.keyframe,
+        })
+    }
+}
+
+/// Composites the frames of a [`Gif`] into RGBA pixels.
+#[wasm_bindgen]
+pub struct FrameIterator {
+    decoder: FrameDecoder<Rc<GifDataStream>>,
+    buffer: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl FrameIterator {
+    /// Composites the next frame, or returns `undefined` once every frame has
+    /// been decoded.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<Option<DecodedFrame>, JsError> {
+        let lsd = &self.decoder.gif().logical_screen_descriptor;
+        let len = lsd.canvas_width as usize
+            * lsd.canvas_height as usize
+            * PixelFormat::Rgba8.bytes_per_pixel();
+        self.buffer.resize(len, 0);
+
+        let Some(meta) = self
+            .decoder
+            .next_frame_into(&mut self.buffer)
+            .map_err(to_js_error)?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(DecodedFrame {
+            index: meta.index,
+            delay: delay_millis(meta.delay_time),
+            delay_time: meta.delay_time,
+            pixels: Uint8ClampedArray::from(self.buffer.as_slice()),
+        }))
+    }
+}
+
+/// A composited frame, covering the entire canvas.
+#[wasm_bindgen]
+pub struct DecodedFrame {
+    pub index: usize,
+
+    /// How long the frame is displayed for, in milliseconds, matching how
+    /// browsers play GIFs back.
+    pub delay: u32,
+
+    /// Delay time of the frame in hundredths of a second, as it appears in
+    /// the data stream, or `undefined` if the frame has none.
+    #[wasm_bindgen(js_name = delayTime)]
+    pub delay_time: Option<u16>,
+
+    pixels: Uint8ClampedArray,
+}
+
+#[wasm_bindgen]
+impl DecodedFrame {
+    /// The pixels of the canvas in RGBA order, ready to be passed to
+    /// `ImageData`.
+    #[wasm_bindgen(getter)]
+    pub fn pixels(&self) -> Uint8ClampedArray {
+        self.pixels.clone()
+    }
+}
+
+fn delay_millis(delay_time: Option<u16>) -> u32 {
+    DelayPolicy::BrowserCompatible
+        .duration(delay_time)
+        .as_millis() as u32
+}
+
+fn to_js_error(err: jif::Error) -> JsError {
+    JsError::new(err.message())
+}
@@ -0,0 +1,54 @@
+#![cfg(target_arch = "wasm32")]
+
+use jif_wasm::Gif;
+use wasm_bindgen_test::*;
+
+const SAMPLE: &[u8] = include_bytes!("../../sample_gifs/sample_2_animation.gif");
+
+#[wasm_bindgen_test]
+fn parse() {
+    let gif = Gif::new(SAMPLE.to_vec(), None).unwrap();
+
+    assert_eq!(gif.version(), "89a");
+    assert!(gif.frame_count() > 1);
+    assert_eq!(gif.frame_metadata().unwrap().len(), gif.frame_count());
+}
+
+#[wasm_bindgen_test]
+fn frames() {
+    let gif = Gif::new(SAMPLE.to_vec(), None).unwrap();
+    let len = gif.width() as u32 * gif.height() as u32 * 4;
+
+    let mut frames = gif.frames().unwrap();
+    let mut count = 0;
+
+    while let Some(frame) = frames.next().unwrap() {
+        assert_eq!(frame.index, count);
+        assert_eq!(
+            frame.delay_time,
+            gif.frame_metadata().unwrap()[count].delay_time
+        );
+        assert_eq!(frame.pixels().length(), len);
+        count += 1;
+    }
+
+    assert_eq!(count, gif.frame_count());
+}
+
+#[wasm_bindgen_test]
+fn invalid() {
+    assert!(Gif::new(b"GIF89a".to_vec(), None).is_err());
+}
+
+#[wasm_bindgen_test]
+fn max_canvas_area() {
+    let gif = Gif::new(SAMPLE.to_vec(), None).unwrap();
+    let area = gif.width() as usize * gif.height() as usize;
+
+    assert!(Gif::new(SAMPLE.to_vec(), Some(area)).is_ok());
+    assert!(Gif::new(SAMPLE.to_vec(), Some(area - 1)).is_err());
+
+    let mut huge = b"GIF89a".to_vec();
+    huge.extend([0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0x3B]);
+    assert!(Gif::new(huge, None).is_err());
+}